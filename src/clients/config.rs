@@ -0,0 +1,87 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::redact::{redact, REDACTED};
+
+use super::{Deluge, InjectOptions, QBittorrent, RTorrent, Transmission, TorrentClient};
+
+/// The kind of a torrent client.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ClientKind {
+    QBittorrent,
+    Transmission,
+    Deluge,
+    RTorrent,
+}
+
+fn default_paused() -> bool {
+    true
+}
+
+/// A torrent client to add the cross-seeds to, from `[clients.<name>]`.
+#[derive(Clone, Deserialize, Serialize)]
+pub struct ClientConfig {
+    #[serde(skip_deserializing)]
+    pub name: String,
+    #[serde(rename = "type")]
+    pub kind: ClientKind,
+    /// The url of the web interface or RPC endpoint. rTorrent also accepts
+    /// `scgi://host:port` and `scgi:///path/to/socket`.
+    pub url: String,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    /// The label in Deluge and rTorrent.
+    pub category: Option<String>,
+    /// Only used by qBittorrent and Transmission.
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default = "default_paused")]
+    pub paused: bool,
+    /// Only used by qBittorrent and Deluge.
+    #[serde(default)]
+    pub skip_checking: bool,
+    /// Only used by Transmission.
+    #[serde(default)]
+    pub verify: bool,
+}
+
+impl fmt::Debug for ClientConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ClientConfig")
+            .field("name", &self.name)
+            .field("kind", &self.kind)
+            .field("url", &redact(&self.url))
+            .field("username", &self.username)
+            .field("password", &self.password.as_ref().map(|_| REDACTED))
+            .field("category", &self.category)
+            .field("tags", &self.tags)
+            .field("paused", &self.paused)
+            .field("skip_checking", &self.skip_checking)
+            .field("verify", &self.verify)
+            .finish()
+    }
+}
+
+impl ClientConfig {
+    pub fn inject_options(&self) -> InjectOptions {
+        InjectOptions {
+            category: self.category.clone(),
+            tags: self.tags.clone(),
+            paused: self.paused,
+            skip_checking: self.skip_checking,
+            verify: self.verify,
+        }
+    }
+
+    /// Create the client, without connecting to it yet.
+    pub fn create_client(&self) -> Box<dyn TorrentClient> {
+        match self.kind {
+            ClientKind::QBittorrent => Box::new(QBittorrent::new(self)),
+            ClientKind::Transmission => Box::new(Transmission::new(self)),
+            ClientKind::Deluge => Box::new(Deluge::new(self)),
+            ClientKind::RTorrent => Box::new(RTorrent::new(self)),
+        }
+    }
+}
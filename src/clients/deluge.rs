@@ -0,0 +1,170 @@
+//! Deluge through the JSON-RPC api of its web interface.
+
+use async_trait::async_trait;
+use reqwest::header::{COOKIE, SET_COOKIE};
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+use tokio::sync::Mutex;
+use tracing::{debug, warn};
+use url::Url;
+
+use super::{ClientConfig, InjectOptions, TorrentClient, TorrentClientError};
+
+/// The error code Deluge uses when the session isn't logged in.
+const NOT_AUTHENTICATED: i64 = 1;
+
+#[derive(Debug, Deserialize)]
+struct RpcResponse {
+    result: Value,
+    error: Option<RpcError>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RpcError {
+    message: String,
+    code: i64,
+}
+
+#[derive(Debug, Deserialize)]
+struct TorrentStatus {
+    save_path: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct Deluge {
+    name: String,
+    http: reqwest::Client,
+    url: String,
+    password: Option<String>,
+    options: InjectOptions,
+    /// The `_session_id` cookie, once logged in.
+    session: Mutex<Option<String>>,
+}
+
+impl Deluge {
+    pub fn new(config: &ClientConfig) -> Self {
+        Deluge {
+            name: config.name.clone(),
+            http: reqwest::Client::new(),
+            url: config.url.clone(),
+            password: config.password.clone(),
+            options: config.inject_options(),
+            session: Mutex::new(None),
+        }
+    }
+
+    fn endpoint(&self) -> Result<Url, TorrentClientError> {
+        let mut url = Url::parse(&self.url)?;
+        if !url.path().trim_end_matches('/').ends_with("/json") {
+            url.path_segments_mut()
+                .map_err(|_| TorrentClientError::InvalidUrl(url::ParseError::RelativeUrlWithCannotBeABaseBase))?
+                .pop_if_empty()
+                .push("json");
+        }
+
+        Ok(url)
+    }
+
+    /// Send one JSON-RPC request with the session cookie.
+    async fn request(&self, session: &Option<String>, method: &str, params: &Value) -> Result<(RpcResponse, Option<String>), TorrentClientError> {
+        let mut request = self.http.post(self.endpoint()?)
+            .json(&json!({ "method": method, "params": params, "id": 1 }));
+        if let Some(cookie) = session {
+            request = request.header(COOKIE, cookie.as_str());
+        }
+
+        let response = request.send().await?.error_for_status()?;
+        let cookie = response.headers().get_all(SET_COOKIE).iter()
+            .filter_map(|value| value.to_str().ok())
+            .filter_map(|value| value.split(';').next())
+            .find(|cookie| cookie.starts_with("_session_id="))
+            .map(str::to_owned);
+
+        Ok((response.json().await?, cookie))
+    }
+
+    /// Log in and make sure the web interface is connected to a daemon.
+    async fn login(&self) -> Result<Option<String>, TorrentClientError> {
+        let password = self.password.as_deref().unwrap_or_default();
+        let (response, cookie) = self.request(&None, "auth.login", &json!([password])).await?;
+        if response.result != Value::Bool(true) || cookie.is_none() {
+            return Err(TorrentClientError::LoginFailed);
+        }
+
+        let session = cookie;
+        let (connected, _) = self.request(&session, "web.connected", &json!([])).await?;
+        if connected.result != Value::Bool(true) {
+            // Connect to the first daemon the web interface knows about.
+            let (hosts, _) = self.request(&session, "web.get_hosts", &json!([])).await?;
+            let host = hosts.result.get(0).and_then(|host| host.get(0)).cloned()
+                .ok_or_else(|| TorrentClientError::InvalidResponse("the web interface has no daemons".to_string()))?;
+
+            debug!("Connecting {} to its daemon", self.name);
+            self.request(&session, "web.connect", &json!([host])).await?;
+        }
+
+        Ok(session)
+    }
+
+    /// Call a method, logging in first or again when the session expired.
+    async fn call<R: DeserializeOwned>(&self, method: &str, params: Value) -> Result<R, TorrentClientError> {
+        let mut session = self.session.lock().await;
+        if session.is_none() {
+            *session = self.login().await?;
+        }
+
+        let (mut response, _) = self.request(&session, method, &params).await?;
+        if response.error.as_ref().is_some_and(|error| error.code == NOT_AUTHENTICATED) {
+            debug!("The session of {} expired, logging in again", self.name);
+            *session = self.login().await?;
+            response = self.request(&session, method, &params).await?.0;
+        }
+
+        if let Some(error) = response.error {
+            return Err(TorrentClientError::Rejected(error.message));
+        }
+
+        serde_json::from_value(response.result)
+            .map_err(|e| TorrentClientError::InvalidResponse(format!("{}: {}", method, e)))
+    }
+}
+
+#[async_trait]
+impl TorrentClient for Deluge {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn save_path(&self, info_hash: &str) -> Result<Option<String>, TorrentClientError> {
+        // Unknown torrents have an empty status.
+        let status: TorrentStatus = self.call("core.get_torrent_status", json!([info_hash, ["save_path"]])).await?;
+
+        Ok(status.save_path)
+    }
+
+    async fn add_torrent(&self, torrent: &[u8], save_path: &str) -> Result<(), TorrentClientError> {
+        let options = json!({
+            "download_location": save_path,
+            "add_paused": self.options.paused,
+            "seed_mode": self.options.skip_checking,
+        });
+
+        let hash: Option<String> = self.call("core.add_torrent_file", json!(["cross-seed.torrent", base64::encode(torrent), options])).await?;
+        let hash = hash.ok_or_else(|| TorrentClientError::Rejected("the torrent wasn't added".to_string()))?;
+
+        // Categories are labels of the label plugin, which have to exist first.
+        if let Some(label) = &self.options.category {
+            let label = label.to_lowercase();
+            if let Err(e) = self.call::<Value>("label.add", json!([label])).await {
+                debug!("Didn't add the label {} to {}: {}", label, self.name, e);
+            }
+            // The torrent was added, a missing label doesn't undo that.
+            if let Err(e) = self.call::<Value>("label.set_torrent", json!([hash, label])).await {
+                warn!("Didn't set the label {} on {} in {}: {}", label, hash, self.name, e);
+            }
+        }
+
+        Ok(())
+    }
+}
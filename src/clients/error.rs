@@ -0,0 +1,57 @@
+use std::fmt;
+
+#[derive(Debug)]
+pub enum TorrentClientError {
+    HttpError(reqwest::Error),
+    /// The client rejected the username or password.
+    LoginFailed,
+    /// The client refused a request, with the reason it gave.
+    Rejected(String),
+    /// The response of the client couldn't be understood.
+    InvalidResponse(String),
+    /// The url of the client couldn't be parsed.
+    InvalidUrl(url::ParseError),
+    /// The client doesn't support the operation.
+    Unsupported(&'static str),
+    /// The connection to an SCGI socket failed.
+    IoError(std::io::Error),
+}
+
+impl fmt::Display for TorrentClientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TorrentClientError::HttpError(e) => write!(f, "http error: {}", e),
+            TorrentClientError::LoginFailed => write!(f, "login failed"),
+            TorrentClientError::Rejected(reason) => write!(f, "rejected: {}", reason),
+            TorrentClientError::InvalidResponse(reason) => write!(f, "invalid response: {}", reason),
+            TorrentClientError::InvalidUrl(e) => write!(f, "invalid url: {}", e),
+            TorrentClientError::Unsupported(operation) => write!(f, "{} isn't supported", operation),
+            TorrentClientError::IoError(e) => write!(f, "io error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for TorrentClientError {}
+
+impl From<reqwest::Error> for TorrentClientError {
+    fn from(mut e: reqwest::Error) -> Self {
+        // The url is part of the display of the error.
+        if let Some(url) = e.url_mut() {
+            crate::redact::redact_url(url);
+        }
+
+        TorrentClientError::HttpError(e)
+    }
+}
+
+impl From<url::ParseError> for TorrentClientError {
+    fn from(e: url::ParseError) -> Self {
+        TorrentClientError::InvalidUrl(e)
+    }
+}
+
+impl From<std::io::Error> for TorrentClientError {
+    fn from(e: std::io::Error) -> Self {
+        TorrentClientError::IoError(e)
+    }
+}
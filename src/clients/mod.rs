@@ -0,0 +1,107 @@
+pub mod error;
+pub use error::*;
+
+pub mod config;
+pub use config::*;
+
+pub mod qbittorrent;
+pub use qbittorrent::*;
+
+pub mod transmission;
+pub use transmission::*;
+
+pub mod deluge;
+pub use deluge::*;
+
+pub mod rtorrent;
+pub use rtorrent::*;
+
+use async_trait::async_trait;
+
+use crate::matching::{Candidate, FileList};
+
+/// How cross-seeds are added to a client.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct InjectOptions {
+    pub category: Option<String>,
+    pub tags: Vec<String>,
+    /// Add the torrents without starting them.
+    pub paused: bool,
+    /// Add the torrents without checking the files first.
+    pub skip_checking: bool,
+    /// Check the files right after adding the torrents, for clients that
+    /// don't check them by themselves.
+    pub verify: bool,
+}
+
+/// A torrent in a client, with what's needed to search for cross-seeds.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClientTorrent {
+    /// The lowercase v1 info hash.
+    pub info_hash: String,
+    pub name: String,
+    pub save_path: String,
+    /// The downloaded part, from 0 to 1.
+    pub progress: f64,
+    /// True if the client can seed it: it's seeding, waiting to seed, or was
+    /// stopped. Torrents that are being checked or have errors can't be.
+    pub seedable: bool,
+    pub category: Option<String>,
+    pub tags: Vec<String>,
+    pub files: FileList,
+}
+
+impl ClientTorrent {
+    /// Only torrents that are fully downloaded and seedable are worth cross-seeding.
+    pub fn is_complete(&self) -> bool {
+        self.progress >= 1.0 && self.seedable
+    }
+}
+
+/// A torrent client that cross-seeds can be added to.
+#[async_trait]
+pub trait TorrentClient: Send + Sync {
+    fn name(&self) -> &str;
+
+    /// The directory a torrent is saved in, or `None` if the client doesn't have the torrent.
+    async fn save_path(&self, info_hash: &str) -> Result<Option<String>, TorrentClientError>;
+
+    /// Add a .torrent file that is saved in `save_path`, using the inject options of the client.
+    async fn add_torrent(&self, torrent: &[u8], save_path: &str) -> Result<(), TorrentClientError>;
+
+    /// The torrents of the client. Clients may leave out the ones that aren't
+    /// complete, when listing their files is expensive.
+    async fn torrents(&self) -> Result<Vec<ClientTorrent>, TorrentClientError> {
+        Err(TorrentClientError::Unsupported("listing torrents"))
+    }
+}
+
+/// The outcome of adding a cross-seed to a client.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InjectOutcome {
+    /// Added at the save path of the original torrent.
+    Added(String),
+    /// The client already has the cross-seed.
+    AlreadyExists,
+    /// The client doesn't have the original torrent.
+    OriginalNotFound,
+}
+
+/// Add a cross-seed to a client next to the original torrent with `original_hash`.
+pub async fn inject(client: &dyn TorrentClient, original_hash: &str, candidate: &Candidate) -> Result<InjectOutcome, TorrentClientError> {
+    match client.save_path(original_hash).await? {
+        Some(save_path) => inject_at(client, &save_path, candidate).await,
+        None => Ok(InjectOutcome::OriginalNotFound),
+    }
+}
+
+/// Add a cross-seed to a client with its data in `save_path`, for data that has no torrent in the client.
+pub async fn inject_at(client: &dyn TorrentClient, save_path: &str, candidate: &Candidate) -> Result<InjectOutcome, TorrentClientError> {
+    if client.save_path(&candidate.torrent.info_hash()).await?.is_some() {
+        return Ok(InjectOutcome::AlreadyExists);
+    }
+
+    client.add_torrent(&candidate.bytes, save_path).await?;
+
+    Ok(InjectOutcome::Added(save_path.to_string()))
+}
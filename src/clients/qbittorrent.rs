@@ -0,0 +1,229 @@
+//! qBittorrent through its WebUI api (v2).
+
+use async_trait::async_trait;
+use reqwest::header::{COOKIE, SET_COOKIE};
+use reqwest::multipart::{Form, Part};
+use reqwest::{RequestBuilder, Response, StatusCode};
+use serde::Deserialize;
+use tokio::sync::Mutex;
+use tracing::debug;
+use url::Url;
+
+use crate::matching::{FileEntry, FileList};
+
+use super::{ClientConfig, ClientTorrent, InjectOptions, TorrentClient, TorrentClientError};
+
+/// The states of finished torrents that are seeding, waiting to seed, or stopped.
+/// qBittorrent 5 renamed `pausedUP` to `stoppedUP`.
+const SEEDABLE_STATES: [&str; 6] = ["uploading", "stalledUP", "forcedUP", "queuedUP", "pausedUP", "stoppedUP"];
+
+#[derive(Debug, Deserialize)]
+struct TorrentInfo {
+    hash: String,
+    name: String,
+    save_path: String,
+    progress: f64,
+    state: String,
+    #[serde(default)]
+    category: String,
+    /// Separated by commas.
+    #[serde(default)]
+    tags: String,
+}
+
+impl TorrentInfo {
+    fn is_seedable(&self) -> bool {
+        SEEDABLE_STATES.contains(&self.state.as_str())
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct TorrentFile {
+    /// The path of the file, including the folder of the torrent.
+    name: String,
+    size: u64,
+}
+
+#[derive(Debug)]
+pub struct QBittorrent {
+    name: String,
+    http: reqwest::Client,
+    url: String,
+    username: Option<String>,
+    password: Option<String>,
+    options: InjectOptions,
+    /// The `SID` cookie of the session, once logged in.
+    session: Mutex<Option<String>>,
+}
+
+impl QBittorrent {
+    pub fn new(config: &ClientConfig) -> Self {
+        QBittorrent {
+            name: config.name.clone(),
+            http: reqwest::Client::new(),
+            url: config.url.clone(),
+            username: config.username.clone(),
+            password: config.password.clone(),
+            options: config.inject_options(),
+            session: Mutex::new(None),
+        }
+    }
+
+    fn endpoint(&self, path: &str) -> Result<Url, TorrentClientError> {
+        let mut url = Url::parse(&self.url)?;
+        url.path_segments_mut()
+            .map_err(|_| TorrentClientError::InvalidUrl(url::ParseError::RelativeUrlWithCannotBeABaseBase))?
+            .pop_if_empty()
+            .extend(["api", "v2"])
+            .extend(path.split('/'));
+
+        Ok(url)
+    }
+
+    /// Log in and return the session cookie. Without a username the client
+    /// must allow requests without authentication, like from localhost.
+    async fn login(&self) -> Result<Option<String>, TorrentClientError> {
+        let username = match &self.username {
+            Some(username) => username,
+            None => return Ok(None),
+        };
+
+        let response = self.http.post(self.endpoint("auth/login")?)
+            .form(&[("username", username.as_str()), ("password", self.password.as_deref().unwrap_or_default())])
+            .send().await?
+            .error_for_status()?;
+
+        let cookie = response.headers().get_all(SET_COOKIE).iter()
+            .filter_map(|value| value.to_str().ok())
+            .filter_map(|value| value.split(';').next())
+            .find(|cookie| cookie.starts_with("SID="))
+            .map(str::to_owned);
+
+        // qBittorrent answers `Fails.` with a successful status code.
+        match (response.text().await?.trim(), cookie) {
+            ("Ok.", Some(cookie)) => Ok(Some(cookie)),
+            _ => Err(TorrentClientError::LoginFailed),
+        }
+    }
+
+    /// Send a request with the session cookie, logging in again when the session expired.
+    async fn send<F>(&self, request: F) -> Result<Response, TorrentClientError>
+    where
+        F: Fn() -> Result<RequestBuilder, TorrentClientError>,
+    {
+        let mut session = self.session.lock().await;
+        if session.is_none() {
+            *session = self.login().await?;
+        }
+
+        let with_cookie = |session: &Option<String>| -> Result<RequestBuilder, TorrentClientError> {
+            let builder = request()?;
+            Ok(match session {
+                Some(cookie) => builder.header(COOKIE, cookie.as_str()),
+                None => builder,
+            })
+        };
+
+        let response = with_cookie(&session)?.send().await?;
+        if response.status() != StatusCode::FORBIDDEN || self.username.is_none() {
+            return Ok(response.error_for_status()?);
+        }
+
+        debug!("The session of {} expired, logging in again", self.name);
+        *session = self.login().await?;
+
+        Ok(with_cookie(&session)?.send().await?.error_for_status()?)
+    }
+}
+
+#[async_trait]
+impl TorrentClient for QBittorrent {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn save_path(&self, info_hash: &str) -> Result<Option<String>, TorrentClientError> {
+        let url = self.endpoint("torrents/info")?;
+        let response = self.send(|| Ok(self.http.get(url.clone()).query(&[("hashes", info_hash)]))).await?;
+
+        let torrents: Vec<TorrentInfo> = response.json().await?;
+
+        Ok(torrents.into_iter().next().map(|torrent| torrent.save_path))
+    }
+
+    async fn add_torrent(&self, torrent: &[u8], save_path: &str) -> Result<(), TorrentClientError> {
+        let url = self.endpoint("torrents/add")?;
+        let options = &self.options;
+
+        let form = || -> Result<RequestBuilder, reqwest::Error> {
+            let file = Part::bytes(torrent.to_vec())
+                .file_name("cross-seed.torrent")
+                .mime_str("application/x-bittorrent")?;
+
+            // `stopped` replaced `paused` in qBittorrent 5.
+            let mut form = Form::new()
+                .part("torrents", file)
+                .text("savepath", save_path.to_string())
+                .text("autoTMM", "false")
+                .text("paused", options.paused.to_string())
+                .text("stopped", options.paused.to_string())
+                .text("skip_checking", options.skip_checking.to_string());
+
+            if let Some(category) = &options.category {
+                form = form.text("category", category.clone());
+            }
+
+            if !options.tags.is_empty() {
+                form = form.text("tags", options.tags.join(","));
+            }
+
+            Ok(self.http.post(url.clone()).multipart(form))
+        };
+
+        let response = self.send(|| Ok(form()?)).await?;
+
+        // qBittorrent answers `Fails.` with a successful status code.
+        match response.text().await?.trim() {
+            "Fails." => Err(TorrentClientError::Rejected("the torrent wasn't added".to_string())),
+            _ => Ok(()),
+        }
+    }
+
+    async fn torrents(&self) -> Result<Vec<ClientTorrent>, TorrentClientError> {
+        let url = self.endpoint("torrents/info")?;
+        let infos: Vec<TorrentInfo> = self.send(|| Ok(self.http.get(url.clone()))).await?.json().await?;
+
+        // Listing the files takes a request per torrent, which is wasted on
+        // torrents that won't be cross-seeded.
+        let infos: Vec<TorrentInfo> = infos.into_iter()
+            .filter(|info| info.progress >= 1.0 && info.is_seedable())
+            .collect();
+
+        let files_url = self.endpoint("torrents/files")?;
+        let mut torrents = Vec::with_capacity(infos.len());
+        for info in infos {
+            let response = self.send(|| Ok(self.http.get(files_url.clone()).query(&[("hash", &info.hash)]))).await?;
+            let files: Vec<TorrentFile> = response.json().await?;
+            let files = files.into_iter()
+                .map(|file| FileEntry { path: file.name.into(), length: file.size })
+                .collect();
+
+            torrents.push(ClientTorrent {
+                info_hash: info.hash.to_lowercase(),
+                seedable: true,
+                progress: info.progress,
+                save_path: info.save_path,
+                category: Some(info.category).filter(|category| !category.is_empty()),
+                tags: info.tags.split(',')
+                    .map(str::trim)
+                    .filter(|tag| !tag.is_empty())
+                    .map(str::to_owned)
+                    .collect(),
+                files: FileList::new(info.name.clone(), files),
+                name: info.name,
+            });
+        }
+
+        Ok(torrents)
+    }
+}
@@ -0,0 +1,231 @@
+//! rTorrent through its XML-RPC api, over HTTP or SCGI.
+
+use std::path::Path;
+
+use async_trait::async_trait;
+use quick_xml::Reader;
+use quick_xml::events::Event;
+use reqwest::StatusCode;
+use reqwest::header::CONTENT_TYPE;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::TcpStream;
+use url::Url;
+
+use super::{ClientConfig, InjectOptions, TorrentClient, TorrentClientError};
+
+/// The fault rTorrent returns for info hashes it doesn't have.
+const UNKNOWN_HASH_FAULT: &str = "Could not find info-hash.";
+
+/// A parameter of an XML-RPC call.
+#[derive(Debug)]
+enum Param<'a> {
+    String(&'a str),
+    Base64(&'a [u8]),
+}
+
+/// The answer to an XML-RPC call.
+#[derive(Debug, PartialEq, Eq)]
+enum Reply {
+    /// The text of the returned scalar value.
+    Value(String),
+    /// The `faultString` of a fault.
+    Fault(String),
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+/// Quote an argument of a command, like the value of `d.directory.set=`.
+/// Backslashes go first so the escapes of quotes aren't escaped again.
+fn quote(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn method_call(method: &str, params: &[Param]) -> String {
+    let mut body = format!("<?xml version=\"1.0\"?><methodCall><methodName>{}</methodName><params>", escape(method));
+    for param in params {
+        let value = match param {
+            Param::String(text) => format!("<string>{}</string>", escape(text)),
+            Param::Base64(data) => format!("<base64>{}</base64>", base64::encode(data)),
+        };
+        body.push_str(&format!("<param><value>{}</value></param>", value));
+    }
+    body.push_str("</params></methodCall>");
+
+    body
+}
+
+/// Parse a method response that returns a scalar value or a fault.
+fn parse_reply(body: &str) -> Result<Reply, TorrentClientError> {
+    let invalid = |e: quick_xml::Error| TorrentClientError::InvalidResponse(format!("invalid xml: {}", e));
+
+    let mut reader = Reader::from_str(body);
+    let mut buf = Vec::new();
+    let mut fault = false;
+    let mut value: Option<String> = None;
+    // The texts of a fault, which is a struct of names and values.
+    let mut texts = Vec::new();
+
+    loop {
+        match reader.read_event(&mut buf).map_err(invalid)? {
+            Event::Start(e) => match e.local_name() {
+                b"fault" => fault = true,
+                b"value" => value = Some(String::new()),
+                _ => {},
+            },
+            Event::Empty(e) if e.local_name() == b"value" && !fault => return Ok(Reply::Value(String::new())),
+            Event::Text(e) => {
+                let text = e.unescape_and_decode(&reader).map_err(invalid)?;
+                if fault && !text.trim().is_empty() {
+                    texts.push(text);
+                } else if let Some(value) = value.as_mut() {
+                    value.push_str(&text);
+                }
+            },
+            Event::End(e) if e.local_name() == b"value" && !fault => {
+                return Ok(Reply::Value(value.take().unwrap_or_default()));
+            },
+            Event::Eof => break,
+            _ => {},
+        }
+
+        buf.clear();
+    }
+
+    let fault_string = texts.iter()
+        .position(|text| text == "faultString")
+        .and_then(|position| texts.get(position + 1));
+
+    match fault_string {
+        Some(fault_string) if fault => Ok(Reply::Fault(fault_string.trim().to_string())),
+        _ => Err(TorrentClientError::InvalidResponse("the response has no value".to_string())),
+    }
+}
+
+/// Send an SCGI request and return the body of the response.
+async fn scgi<S: AsyncRead + AsyncWrite + Unpin>(mut stream: S, body: &[u8]) -> Result<Vec<u8>, TorrentClientError> {
+    let headers = format!("CONTENT_LENGTH\0{}\0SCGI\01\0REQUEST_METHOD\0POST\0REQUEST_URI\0/RPC2\0", body.len());
+    stream.write_all(format!("{}:{},", headers.len(), headers).as_bytes()).await?;
+    stream.write_all(body).await?;
+
+    let mut response = Vec::new();
+    stream.read_to_end(&mut response).await?;
+
+    // The response has CGI headers, like `Status: 200 OK`.
+    let body_start = response.windows(4).position(|w| w == b"\r\n\r\n")
+        .ok_or_else(|| TorrentClientError::InvalidResponse("the SCGI response has no headers".to_string()))?;
+
+    Ok(response.split_off(body_start + 4))
+}
+
+#[derive(Debug)]
+pub struct RTorrent {
+    name: String,
+    http: reqwest::Client,
+    /// An http(s) url of the XML-RPC endpoint, or `scgi://host:port` or `scgi:///path/to/socket`.
+    url: String,
+    username: Option<String>,
+    password: Option<String>,
+    options: InjectOptions,
+}
+
+impl RTorrent {
+    pub fn new(config: &ClientConfig) -> Self {
+        RTorrent {
+            name: config.name.clone(),
+            http: reqwest::Client::new(),
+            url: config.url.clone(),
+            username: config.username.clone(),
+            password: config.password.clone(),
+            options: config.inject_options(),
+        }
+    }
+
+    async fn send(&self, body: String) -> Result<Vec<u8>, TorrentClientError> {
+        let url = Url::parse(&self.url)?;
+        if url.scheme() != "scgi" {
+            let mut request = self.http.post(url)
+                .header(CONTENT_TYPE, "text/xml")
+                .body(body);
+            if let Some(username) = &self.username {
+                request = request.basic_auth(username, self.password.as_ref());
+            }
+
+            let response = request.send().await?;
+            if response.status() == StatusCode::UNAUTHORIZED {
+                return Err(TorrentClientError::LoginFailed);
+            }
+
+            return Ok(response.error_for_status()?.bytes().await?.to_vec());
+        }
+
+        match (url.host_str(), url.port()) {
+            (Some(host), Some(port)) => scgi(TcpStream::connect((host, port)).await?, body.as_bytes()).await,
+            #[cfg(unix)]
+            (None, _) => scgi(tokio::net::UnixStream::connect(url.path()).await?, body.as_bytes()).await,
+            _ => Err(TorrentClientError::InvalidUrl(url::ParseError::InvalidPort)),
+        }
+    }
+
+    async fn call(&self, method: &str, params: &[Param<'_>]) -> Result<Reply, TorrentClientError> {
+        let response = self.send(method_call(method, params)).await?;
+        let body = std::str::from_utf8(&response)
+            .map_err(|e| TorrentClientError::InvalidResponse(e.to_string()))?;
+
+        parse_reply(body)
+    }
+
+    /// Call a method of a torrent, `None` if rTorrent doesn't have the torrent.
+    async fn torrent_call(&self, method: &str, info_hash: &str) -> Result<Option<String>, TorrentClientError> {
+        // rTorrent only knows uppercase info hashes.
+        match self.call(method, &[Param::String(&info_hash.to_uppercase())]).await? {
+            Reply::Value(value) => Ok(Some(value)),
+            Reply::Fault(fault) if fault == UNKNOWN_HASH_FAULT => Ok(None),
+            Reply::Fault(fault) => Err(TorrentClientError::Rejected(fault)),
+        }
+    }
+}
+
+#[async_trait]
+impl TorrentClient for RTorrent {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn save_path(&self, info_hash: &str) -> Result<Option<String>, TorrentClientError> {
+        let directory = match self.torrent_call("d.directory", info_hash).await? {
+            Some(directory) => directory,
+            None => return Ok(None),
+        };
+
+        // The directory of a multi file torrent includes its own folder.
+        if self.torrent_call("d.is_multi_file", info_hash).await?.as_deref() == Some("1") {
+            let parent = Path::new(&directory).parent()
+                .ok_or_else(|| TorrentClientError::InvalidResponse(format!("{} has no parent directory", directory)))?;
+
+            return Ok(Some(parent.to_string_lossy().into_owned()));
+        }
+
+        Ok(Some(directory))
+    }
+
+    async fn add_torrent(&self, torrent: &[u8], save_path: &str) -> Result<(), TorrentClientError> {
+        let method = if self.options.paused { "load.raw" } else { "load.raw_start" };
+
+        // Like `d.directory`, multi file torrents are saved in a folder inside it.
+        let directory = format!("d.directory.set={}", quote(save_path));
+        let label = self.options.category.as_ref()
+            .map(|category| format!("d.custom1.set={}", quote(category)));
+
+        let mut params = vec![Param::String(""), Param::Base64(torrent), Param::String(&directory)];
+        if let Some(label) = &label {
+            params.push(Param::String(label));
+        }
+
+        match self.call(method, &params).await? {
+            Reply::Value(_) => Ok(()),
+            Reply::Fault(fault) => Err(TorrentClientError::Rejected(fault)),
+        }
+    }
+}
@@ -0,0 +1,246 @@
+//! Transmission through its JSON RPC api.
+
+use async_trait::async_trait;
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use serde::de::DeserializeOwned;
+use tokio::sync::Mutex;
+use tracing::debug;
+
+use crate::matching::{FileEntry, FileList};
+
+use super::{ClientConfig, ClientTorrent, InjectOptions, TorrentClient, TorrentClientError};
+
+/// The header with the id that protects the RPC api against CSRF.
+const SESSION_ID_HEADER: &str = "X-Transmission-Session-Id";
+
+/// The statuses of torrents that are stopped, waiting to seed, or seeding.
+/// Stopped downloads are told apart by their progress.
+const SEEDABLE_STATUSES: [u8; 3] = [0, 5, 6];
+
+#[derive(Debug, Serialize)]
+struct RpcRequest<A> {
+    method: &'static str,
+    arguments: A,
+}
+
+#[derive(Debug, Deserialize)]
+struct RpcResponse<A> {
+    /// `success` or the error.
+    result: String,
+    arguments: Option<A>,
+}
+
+#[derive(Debug, Serialize)]
+struct TorrentGet<'a> {
+    fields: &'a [&'a str],
+    /// All torrents when left out.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    ids: Option<&'a [&'a str]>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Torrents {
+    torrents: Vec<TorrentInfo>,
+}
+
+#[derive(Debug, Deserialize)]
+struct FullTorrents {
+    torrents: Vec<FullTorrentInfo>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TorrentInfo {
+    download_dir: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FullTorrentInfo {
+    hash_string: String,
+    name: String,
+    download_dir: String,
+    percent_done: f64,
+    status: u8,
+    #[serde(default)]
+    labels: Vec<String>,
+    files: Vec<TorrentFile>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TorrentFile {
+    /// The path of the file, including the folder of the torrent.
+    name: String,
+    length: u64,
+}
+
+#[derive(Debug, Serialize)]
+struct TorrentAdd<'a> {
+    /// The base64 encoded .torrent file.
+    metainfo: String,
+    #[serde(rename = "download-dir")]
+    download_dir: &'a str,
+    paused: bool,
+    #[serde(skip_serializing_if = "<[String]>::is_empty")]
+    labels: &'a [String],
+}
+
+#[derive(Debug, Deserialize)]
+struct TorrentAdded {
+    #[serde(rename = "torrent-added")]
+    added: Option<AddedTorrent>,
+    #[serde(rename = "torrent-duplicate")]
+    duplicate: Option<AddedTorrent>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AddedTorrent {
+    hash_string: String,
+}
+
+#[derive(Debug, Serialize)]
+struct TorrentVerify<'a> {
+    ids: &'a [&'a str],
+}
+
+#[derive(Debug)]
+pub struct Transmission {
+    name: String,
+    http: reqwest::Client,
+    url: String,
+    username: Option<String>,
+    password: Option<String>,
+    options: InjectOptions,
+    session_id: Mutex<Option<String>>,
+}
+
+impl Transmission {
+    pub fn new(config: &ClientConfig) -> Self {
+        Transmission {
+            name: config.name.clone(),
+            http: reqwest::Client::new(),
+            url: config.url.clone(),
+            username: config.username.clone(),
+            password: config.password.clone(),
+            options: config.inject_options(),
+            session_id: Mutex::new(None),
+        }
+    }
+
+    /// Call an RPC method, getting a new session id when Transmission asks for one.
+    async fn call<A, R>(&self, method: &'static str, arguments: A) -> Result<R, TorrentClientError>
+    where
+        A: Serialize,
+        R: DeserializeOwned,
+    {
+        let body = RpcRequest { method, arguments };
+        let mut session_id = self.session_id.lock().await;
+
+        // The first request of a session is answered with 409 and the id to use.
+        let mut attempts = 0;
+        let response = loop {
+            let mut request = self.http.post(&self.url).json(&body);
+            if let Some(id) = session_id.as_deref() {
+                request = request.header(SESSION_ID_HEADER, id);
+            }
+            if let Some(username) = &self.username {
+                request = request.basic_auth(username, self.password.as_ref());
+            }
+
+            let response = request.send().await?;
+            if response.status() != StatusCode::CONFLICT || attempts > 0 {
+                break response;
+            }
+
+            debug!("Getting a new session id from {}", self.name);
+            *session_id = response.headers().get(SESSION_ID_HEADER)
+                .and_then(|id| id.to_str().ok())
+                .map(str::to_owned);
+            attempts += 1;
+        };
+
+        if response.status() == StatusCode::UNAUTHORIZED {
+            return Err(TorrentClientError::LoginFailed);
+        }
+
+        let response: RpcResponse<R> = response.error_for_status()?.json().await?;
+        if response.result != "success" {
+            return Err(TorrentClientError::Rejected(response.result));
+        }
+
+        response.arguments
+            .ok_or_else(|| TorrentClientError::InvalidResponse(format!("{} returned no arguments", method)))
+    }
+}
+
+#[async_trait]
+impl TorrentClient for Transmission {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn save_path(&self, info_hash: &str) -> Result<Option<String>, TorrentClientError> {
+        let arguments = TorrentGet {
+            fields: &["hashString", "downloadDir"],
+            ids: Some(&[info_hash]),
+        };
+        let torrents: Torrents = self.call("torrent-get", arguments).await?;
+
+        Ok(torrents.torrents.into_iter().next().map(|torrent| torrent.download_dir))
+    }
+
+    async fn add_torrent(&self, torrent: &[u8], save_path: &str) -> Result<(), TorrentClientError> {
+        let arguments = TorrentAdd {
+            metainfo: base64::encode(torrent),
+            download_dir: save_path,
+            paused: self.options.paused,
+            labels: &self.options.tags,
+        };
+        let added: TorrentAdded = self.call("torrent-add", arguments).await?;
+
+        let hash = match (added.added, added.duplicate) {
+            (Some(torrent), _) => torrent.hash_string,
+            (None, Some(_)) => return Err(TorrentClientError::Rejected("the torrent already exists".to_string())),
+            (None, None) => return Err(TorrentClientError::InvalidResponse("torrent-add returned no torrent".to_string())),
+        };
+
+        // Check the existing files before seeding them.
+        if self.options.verify {
+            let _: serde::de::IgnoredAny = self.call("torrent-verify", TorrentVerify { ids: &[&hash] }).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn torrents(&self) -> Result<Vec<ClientTorrent>, TorrentClientError> {
+        let arguments = TorrentGet {
+            fields: &["hashString", "name", "downloadDir", "percentDone", "status", "labels", "files"],
+            ids: None,
+        };
+        let response: FullTorrents = self.call("torrent-get", arguments).await?;
+
+        let torrents = response.torrents.into_iter()
+            .map(|torrent| {
+                let files = torrent.files.into_iter()
+                    .map(|file| FileEntry { path: file.name.into(), length: file.length })
+                    .collect();
+
+                // Transmission only has labels, which are used like tags.
+                ClientTorrent {
+                    info_hash: torrent.hash_string.to_lowercase(),
+                    save_path: torrent.download_dir,
+                    progress: torrent.percent_done,
+                    seedable: SEEDABLE_STATUSES.contains(&torrent.status),
+                    category: None,
+                    tags: torrent.labels,
+                    files: FileList::new(torrent.name.clone(), files),
+                    name: torrent.name,
+                }
+            })
+            .collect();
+
+        Ok(torrents)
+    }
+}
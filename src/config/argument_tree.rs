@@ -87,7 +87,13 @@ impl ArgumentTree {
     }
 
     /// Convert the tree into a `Dict`.
-    pub fn to_dict(&mut self) -> Dict {
+    pub fn to_dict(&self) -> Dict {
         self.root.to_dict()
     }
+}
+
+impl Default for ArgumentTree {
+    fn default() -> Self {
+        Self::new()
+    }
 }
\ No newline at end of file
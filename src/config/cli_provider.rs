@@ -2,6 +2,7 @@ use figment::{Provider, Metadata, Profile, Error};
 use figment::value::{Map, Dict, Value, Tag};
 
 use crate::config::ArgumentTree;
+use crate::redact::redact;
 
 /// A provider that fetches its data from a given URL.
 pub struct CliProvider {
@@ -19,13 +20,19 @@ impl CliProvider {
     }
 }
 
+impl Default for CliProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Provider for CliProvider {
     /// Returns metadata with kind `Cli Flags`, custom source is the 
-    /// command line arguments separated by spaces.
+    /// command line arguments separated by spaces, with secrets redacted.
     fn metadata(&self) -> Metadata {
         let args = &self.args;
         Metadata::named("Cli Flags")
-            .source(args.join(" "))
+            .source(redact(&args.join(" ")))
     }
 
     /// Parses the command line arguments into a `Map` and `Value`s.
@@ -42,7 +49,9 @@ impl Provider for CliProvider {
             }
         }
 
-        fn parse_cli(args: &Vec<std::string::String>)-> Result<Dict, Error> {
+        // The error type is dictated by the `Provider` trait.
+        #[allow(clippy::result_large_err)]
+        fn parse_cli(args: &[std::string::String])-> Result<Dict, Error> {
             // TODO: Parse _args as booleans
             let (_args, argv) = argmap::parse(args.iter());
 
@@ -1,57 +1,134 @@
 use serde::{Deserialize,Serialize};
 use std::path::Path;
+use std::time::Duration;
 use std::env;
 use std::collections::HashMap;
+use std::fmt;
 use figment::{Figment, providers::{Format, Toml, Env}};
 use figment::value::Value as FigmentValue;
 
-use crate::torznab::TorznabClient;
+use crate::clients::ClientConfig;
+use crate::discovery::Discovery;
+use crate::output::{OutputTemplate, TemplateError};
+use crate::redact::{redact, REDACTED};
+use crate::torznab::{TorznabClient, RateLimit, RetryPolicy};
 
 use super::CliProvider;
 
+/// The default amount of searches that can run at the same time.
+pub const DEFAULT_MAX_CONCURRENCY: usize = 10;
+/// The default amount of searches that can run at the same time on one indexer.
+pub const DEFAULT_INDEXER_CONCURRENCY: usize = 2;
+/// The default maximum amount of results of a search, across all pages.
+pub const DEFAULT_MAX_SEARCH_RESULTS: usize = 100;
+/// The default path of the state database.
+pub const DEFAULT_STATE_PATH: &str = "cross-seed.db";
+/// The default amount of hours before a torrent is searched on an indexer again.
+pub const DEFAULT_RESEARCH_INTERVAL_HOURS: u64 = 24;
+
 #[derive(Deserialize, Serialize)]
 pub struct Config {
     /// The path of the torrents to search.
-    torrents_path: String,
+    torrents_path: Option<String>,
+    /// The name of a client in `[clients]` to get the torrents to search from.
+    /// It can be combined with `torrents_path` and `data_paths`, a torrent found
+    /// in more than one of them is searched once.
+    source_client: Option<String>,
+    /// Directories with data to search, where each file or folder at the top is one torrent.
+    #[serde(default)]
+    data_paths: Vec<String>,
     /// The output path of the torrents.
     output_path: Option<String>,
+    /// The file name template of the saved torrents.
+    output_template: Option<String>,
+    /// The maximum amount of searches running at the same time.
+    max_concurrency: Option<usize>,
+    /// The maximum amount of results of a search, across all pages.
+    max_search_results: Option<usize>,
+    /// The path of a TOML file with ids for torrents that don't contain them.
+    id_map_path: Option<String>,
+    /// The path of the database that remembers earlier searches.
+    state_path: Option<String>,
+    /// The amount of hours before a torrent is searched on an indexer again.
+    research_interval_hours: Option<u64>,
     
     //pub indexers: HashMap<String, Indexer>,
 
     
     /// Used for deserializing the indexers into a Vec<Indexer>.
-    #[serde(rename = "indexers")]
+    #[serde(rename = "indexers", default)]
     indexers_map: HashMap<String, FigmentValue>,
 
+    /// Jackett and Prowlarr instances to get more indexers from.
+    #[serde(default)]
+    discovery: HashMap<String, Discovery>,
+
+    /// Torrent clients to add the cross-seeds to.
+    #[serde(default)]
+    clients: HashMap<String, ClientConfig>,
+
     /// The indexers to search.
     #[serde(skip)]
     pub indexers: Vec<Indexer>,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Clone, Deserialize, Serialize)]
 pub struct Indexer {
     #[serde(skip_deserializing)]
     pub name: String,
     pub enabled: Option<bool>,
     pub url: String,
     pub api_key: String,
+    /// The maximum amount of searches running at the same time on this indexer.
+    pub max_concurrency: Option<usize>,
+    /// Limits the requests sent to this indexer.
+    pub rate_limit: Option<RateLimit>,
+    /// How failed requests to this indexer are retried.
+    #[serde(default)]
+    pub retry: RetryPolicy,
 
     #[serde(skip)]
     pub client: Option<TorznabClient>,
 }
 
+impl fmt::Debug for Indexer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Indexer")
+            .field("name", &self.name)
+            .field("enabled", &self.enabled)
+            .field("url", &redact(&self.url))
+            .field("api_key", &REDACTED)
+            .field("max_concurrency", &self.max_concurrency)
+            .field("rate_limit", &self.rate_limit)
+            .field("retry", &self.retry)
+            .field("client", &self.client)
+            .finish()
+    }
+}
+
 impl Indexer {
     pub async fn create_client(&mut self) -> Result<&TorznabClient, crate::torznab::ClientError> {
         if self.client.is_none() {
-            self.client = Some(TorznabClient::new(self.name.clone(), &self.url, &self.api_key).await?);
+            let mut client = TorznabClient::new_no_capabilities(self.name.clone(), &self.url, &self.api_key)
+                .with_retry_policy(self.retry.clone());
+            if let Some(rate_limit) = &self.rate_limit {
+                client = client.with_rate_limit(rate_limit);
+            }
+
+            client.store_capabilities().await?;
+            self.client = Some(client);
         }
 
         Ok(self.client.as_ref().unwrap())
     }
+
+    pub fn max_concurrency(&self) -> usize {
+        self.max_concurrency.unwrap_or(DEFAULT_INDEXER_CONCURRENCY).max(1)
+    }
 }
 
 // Allow dead code for functions. We should probably remove this later on.
-#[allow(dead_code)]
+#[allow(dead_code, clippy::new_without_default)]
 impl Config {
     pub fn new() -> Config {
         // The path of the config file without the file extension
@@ -68,25 +145,52 @@ impl Config {
             .join(Env::prefixed("CROSS_SEED_"))
             .join(Toml::file(format!("{}.toml", path)));
 
-        let mut config: Config = figment.extract().unwrap();
+        Config::from_figment(figment).unwrap()
+    }
+
+    /// Construct the config from any figment, like a TOML string.
+    // The error type is figment's own.
+    #[allow(clippy::result_large_err)]
+    pub fn from_figment(figment: Figment) -> Result<Config, figment::Error> {
+        let mut config: Config = figment.extract()?;
 
         // Parse the indexers map into a vector.
         for (name, value) in &mut config.indexers_map {
-            let mut indexer: Indexer = value.deserialize().unwrap();
+            let mut indexer: Indexer = value.deserialize()?;
             indexer.name = name.to_owned();
 
             config.indexers.push(indexer);
         }
 
-        config
+        for (name, discovery) in &mut config.discovery {
+            discovery.name = name.to_owned();
+        }
+
+        for (name, client) in &mut config.clients {
+            client.name = name.to_owned();
+        }
+
+        Ok(config)
     }
 
-    pub fn torrents_path(&self) -> &Path {
-        Path::new(&self.torrents_path)
+    pub fn torrents_path(&self) -> Option<&Path> {
+        self.torrents_path.as_deref().map(Path::new)
     }
 
-    pub fn torrents_path_str(&self) -> &String {
-        &self.torrents_path
+    pub fn torrents_path_str(&self) -> Option<&String> {
+        self.torrents_path.as_ref()
+    }
+
+    pub fn source_client(&self) -> Option<&str> {
+        self.source_client.as_deref()
+    }
+
+    pub fn data_paths(&self) -> impl Iterator<Item = &Path> {
+        self.data_paths.iter().map(Path::new)
+    }
+
+    pub fn client(&self, name: &str) -> Option<&ClientConfig> {
+        self.clients.get(name)
     }
 
     pub fn output_path(&self) -> Option<&Path> {
@@ -99,4 +203,40 @@ impl Config {
     pub fn output_path_str(&self) -> Option<&String> {
         self.output_path.as_ref()
     }
+
+    pub fn max_concurrency(&self) -> usize {
+        self.max_concurrency.unwrap_or(DEFAULT_MAX_CONCURRENCY).max(1)
+    }
+
+    pub fn max_search_results(&self) -> usize {
+        self.max_search_results.unwrap_or(DEFAULT_MAX_SEARCH_RESULTS)
+    }
+
+    pub fn discovery(&self) -> impl Iterator<Item = &Discovery> {
+        self.discovery.values()
+    }
+
+    pub fn clients(&self) -> impl Iterator<Item = &ClientConfig> {
+        self.clients.values()
+    }
+
+    pub fn id_map_path(&self) -> Option<&Path> {
+        self.id_map_path.as_deref().map(Path::new)
+    }
+
+    pub fn state_path(&self) -> &Path {
+        Path::new(self.state_path.as_deref().unwrap_or(DEFAULT_STATE_PATH))
+    }
+
+    pub fn research_interval(&self) -> Duration {
+        let hours = self.research_interval_hours.unwrap_or(DEFAULT_RESEARCH_INTERVAL_HOURS);
+        Duration::from_secs(hours.saturating_mul(60 * 60))
+    }
+
+    pub fn output_template(&self) -> Result<OutputTemplate, TemplateError> {
+        match self.output_template {
+            Some(ref template) => template.parse(),
+            None => Ok(OutputTemplate::default()),
+        }
+    }
 }
\ No newline at end of file
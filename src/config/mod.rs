@@ -1,5 +1,6 @@
+#[allow(clippy::module_inception)]
 pub mod config;
-pub use config::Config;
+pub use config::{Config, Indexer};
 
 pub mod argument_tree;
 pub use argument_tree::*;
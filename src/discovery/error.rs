@@ -0,0 +1,45 @@
+use std::fmt;
+
+#[derive(Debug)]
+pub enum DiscoveryError {
+    HttpError(reqwest::Error),
+    /// The indexer list of Jackett couldn't be parsed.
+    XmlError(quick_xml::DeError),
+    /// The url of the manager couldn't be parsed.
+    InvalidUrl(url::ParseError),
+}
+
+impl fmt::Display for DiscoveryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DiscoveryError::HttpError(e) => write!(f, "http error: {}", e),
+            DiscoveryError::XmlError(e) => write!(f, "invalid xml: {}", e),
+            DiscoveryError::InvalidUrl(e) => write!(f, "invalid url: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for DiscoveryError {}
+
+impl From<reqwest::Error> for DiscoveryError {
+    fn from(mut e: reqwest::Error) -> Self {
+        // The url is part of the display of the error.
+        if let Some(url) = e.url_mut() {
+            crate::redact::redact_url(url);
+        }
+
+        DiscoveryError::HttpError(e)
+    }
+}
+
+impl From<quick_xml::DeError> for DiscoveryError {
+    fn from(e: quick_xml::DeError) -> Self {
+        DiscoveryError::XmlError(e)
+    }
+}
+
+impl From<url::ParseError> for DiscoveryError {
+    fn from(e: url::ParseError) -> Self {
+        DiscoveryError::InvalidUrl(e)
+    }
+}
@@ -0,0 +1,49 @@
+//! Jackett lists its indexers through the `t=indexers` function of the
+//! Torznab endpoint of the `all` indexer.
+
+use serde::Deserialize;
+use url::Url;
+
+use super::{DiscoveredIndexer, Discovery, DiscoveryError};
+
+#[derive(Debug, Deserialize)]
+struct Indexers {
+    #[serde(rename = "indexer", default)]
+    indexers: Vec<Indexer>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Indexer {
+    id: String,
+    #[serde(default)]
+    configured: bool,
+    title: String,
+}
+
+/// The Torznab url of a Jackett indexer.
+pub fn torznab_url(discovery: &Discovery, id: &str) -> Result<Url, DiscoveryError> {
+    discovery.url(&["api", "v2.0", "indexers", id, "results", "torznab", "api"])
+}
+
+/// List the configured indexers of Jackett.
+pub async fn list(http: &reqwest::Client, discovery: &Discovery) -> Result<Vec<DiscoveredIndexer>, DiscoveryError> {
+    let mut url = torznab_url(discovery, "all")?;
+    url.query_pairs_mut()
+        .append_pair("apikey", &discovery.api_key)
+        .append_pair("t", "indexers")
+        .append_pair("configured", "true");
+
+    let body = http.get(url).send().await?
+        .error_for_status()?
+        .text().await?;
+
+    let list: Indexers = quick_xml::de::from_str(&body)?;
+
+    Ok(list.indexers.into_iter()
+        .filter(|indexer| indexer.configured)
+        .map(|indexer| DiscoveredIndexer {
+            id: indexer.id,
+            name: indexer.title,
+        })
+        .collect())
+}
@@ -0,0 +1,119 @@
+pub mod error;
+pub use error::*;
+
+pub mod jackett;
+pub mod prowlarr;
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::config::Indexer;
+use crate::redact::{redact, REDACTED};
+use crate::torznab::{RateLimit, RetryPolicy};
+
+/// The kind of indexer manager to discover indexers from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DiscoveryKind {
+    Jackett,
+    Prowlarr,
+}
+
+/// An indexer listed by an indexer manager.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiscoveredIndexer {
+    /// The id of the indexer in the manager, used in its Torznab url.
+    pub id: String,
+    pub name: String,
+}
+
+/// A Jackett or Prowlarr instance to get the indexers from.
+#[derive(Clone, Deserialize, Serialize)]
+pub struct Discovery {
+    #[serde(skip_deserializing)]
+    pub name: String,
+    #[serde(rename = "type")]
+    pub kind: DiscoveryKind,
+    /// The base url of the manager, like `http://localhost:9117`.
+    pub url: String,
+    pub api_key: String,
+    /// Only use the indexers with these names. All indexers are used when empty.
+    #[serde(default)]
+    pub include: Vec<String>,
+    /// Never use the indexers with these names.
+    #[serde(default)]
+    pub exclude: Vec<String>,
+    /// Used for all the discovered indexers.
+    pub max_concurrency: Option<usize>,
+    /// Used for all the discovered indexers.
+    pub rate_limit: Option<RateLimit>,
+    /// Used for all the discovered indexers.
+    #[serde(default)]
+    pub retry: RetryPolicy,
+}
+
+impl fmt::Debug for Discovery {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Discovery")
+            .field("name", &self.name)
+            .field("kind", &self.kind)
+            .field("url", &redact(&self.url))
+            .field("api_key", &REDACTED)
+            .field("include", &self.include)
+            .field("exclude", &self.exclude)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Discovery {
+    /// List the indexers of the manager that pass the filters.
+    pub async fn indexers(&self) -> Result<Vec<Indexer>, DiscoveryError> {
+        let http = reqwest::Client::new();
+        let listed = match self.kind {
+            DiscoveryKind::Jackett => jackett::list(&http, self).await?,
+            DiscoveryKind::Prowlarr => prowlarr::list(&http, self).await?,
+        };
+
+        let mut indexers = Vec::new();
+        for discovered in listed.into_iter().filter(|indexer| self.is_wanted(&indexer.name)) {
+            let url = match self.kind {
+                DiscoveryKind::Jackett => jackett::torznab_url(self, &discovered.id)?,
+                DiscoveryKind::Prowlarr => prowlarr::torznab_url(self, &discovered.id)?,
+            };
+
+            indexers.push(Indexer {
+                name: discovered.name,
+                enabled: Some(true),
+                url: url.to_string(),
+                api_key: self.api_key.clone(),
+                max_concurrency: self.max_concurrency,
+                rate_limit: self.rate_limit.clone(),
+                retry: self.retry.clone(),
+                client: None,
+            });
+        }
+
+        Ok(indexers)
+    }
+
+    /// Returns true if the include and exclude filters allow an indexer.
+    pub fn is_wanted(&self, name: &str) -> bool {
+        let matches = |filter: &String| filter.eq_ignore_ascii_case(name);
+
+        (self.include.is_empty() || self.include.iter().any(matches))
+            && !self.exclude.iter().any(matches)
+    }
+
+    /// The base url with extra path segments.
+    fn url(&self, segments: &[&str]) -> Result<Url, DiscoveryError> {
+        let mut url = Url::parse(&self.url)?;
+        url.path_segments_mut()
+            .map_err(|_| DiscoveryError::InvalidUrl(url::ParseError::RelativeUrlWithCannotBeABaseBase))?
+            .pop_if_empty()
+            .extend(segments);
+
+        Ok(url)
+    }
+}
@@ -0,0 +1,41 @@
+//! Prowlarr lists its indexers through its v1 api, and serves a Torznab
+//! endpoint for each of them at `/<id>/api`.
+
+use serde::Deserialize;
+use url::Url;
+
+use super::{DiscoveredIndexer, Discovery, DiscoveryError};
+
+#[derive(Debug, Deserialize)]
+struct Indexer {
+    id: u32,
+    name: String,
+    #[serde(default)]
+    enable: bool,
+    /// `torrent` or `usenet`.
+    protocol: String,
+}
+
+/// The Torznab url of a Prowlarr indexer.
+pub fn torznab_url(discovery: &Discovery, id: &str) -> Result<Url, DiscoveryError> {
+    discovery.url(&[id, "api"])
+}
+
+/// List the enabled torrent indexers of Prowlarr.
+pub async fn list(http: &reqwest::Client, discovery: &Discovery) -> Result<Vec<DiscoveredIndexer>, DiscoveryError> {
+    let url = discovery.url(&["api", "v1", "indexer"])?;
+
+    let indexers: Vec<Indexer> = http.get(url)
+        .header("X-Api-Key", &discovery.api_key)
+        .send().await?
+        .error_for_status()?
+        .json().await?;
+
+    Ok(indexers.into_iter()
+        .filter(|indexer| indexer.enable && indexer.protocol == "torrent")
+        .map(|indexer| DiscoveredIndexer {
+            id: indexer.id.to_string(),
+            name: indexer.name,
+        })
+        .collect())
+}
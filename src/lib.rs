@@ -0,0 +1,12 @@
+pub mod config;
+pub mod torznab;
+pub mod matching;
+pub mod output;
+pub mod release;
+pub mod scheduler;
+pub mod state;
+pub mod redact;
+pub mod discovery;
+pub mod pipeline;
+pub mod clients;
+pub mod source;
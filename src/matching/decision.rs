@@ -0,0 +1,108 @@
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MatchDecision {
+    /// Every file matches by path and size.
+    Match,
+    /// Every file matches by size, but some of the paths differ.
+    SizeOnly,
+    /// Only some of the files match.
+    Partial,
+    Mismatch,
+}
+
+impl MatchDecision {
+    /// Returns true if the candidate can be cross-seeded.
+    pub fn is_match(&self) -> bool {
+        matches!(self, MatchDecision::Match | MatchDecision::SizeOnly)
+    }
+
+    /// A short identifier of the decision, used when storing it.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MatchDecision::Match => "match",
+            MatchDecision::SizeOnly => "size_only",
+            MatchDecision::Partial => "partial",
+            MatchDecision::Mismatch => "mismatch",
+        }
+    }
+}
+
+impl FromStr for MatchDecision {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "match" => Ok(MatchDecision::Match),
+            "size_only" => Ok(MatchDecision::SizeOnly),
+            "partial" => Ok(MatchDecision::Partial),
+            "mismatch" => Ok(MatchDecision::Mismatch),
+            _ => Err(format!("Unknown match decision: {}", s)),
+        }
+    }
+}
+
+impl fmt::Display for MatchDecision {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            MatchDecision::Match => "match",
+            MatchDecision::SizeOnly => "size-only match",
+            MatchDecision::Partial => "partial match",
+            MatchDecision::Mismatch => "mismatch",
+        };
+
+        write!(f, "{}", s)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MatchReason {
+    /// All files have the same path and size.
+    IdenticalFiles { files: usize },
+    /// All files have the same size, but `differing` of them have another path.
+    DifferentPaths { differing: usize },
+    /// Only `matched` of the candidate's files were found locally.
+    SomeFilesMatch { matched: usize, total: usize },
+    /// The size reported by the indexer is too far from the local size.
+    SizeDiffers { local: u64, candidate: u64 },
+    /// The candidate is the same torrent that is being searched for.
+    SameInfoHash,
+    NoFilesMatch,
+}
+
+impl fmt::Display for MatchReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MatchReason::IdenticalFiles { files } => write!(f, "all {} files are identical", files),
+            MatchReason::DifferentPaths { differing } => write!(f, "all sizes match but {} paths differ", differing),
+            MatchReason::SomeFilesMatch { matched, total } => write!(f, "{} of {} files match", matched, total),
+            MatchReason::SizeDiffers { local, candidate } => write!(f, "size differs (local: {}, candidate: {})", local, candidate),
+            MatchReason::SameInfoHash => write!(f, "candidate has the same info hash"),
+            MatchReason::NoFilesMatch => write!(f, "no files match"),
+        }
+    }
+}
+
+/// The decision of comparing a candidate against a local torrent.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MatchResult {
+    pub decision: MatchDecision,
+    pub reason: MatchReason,
+}
+
+impl MatchResult {
+    pub fn new(decision: MatchDecision, reason: MatchReason) -> Self {
+        MatchResult { decision, reason }
+    }
+
+    pub fn mismatch(reason: MatchReason) -> Self {
+        MatchResult::new(MatchDecision::Mismatch, reason)
+    }
+}
+
+impl fmt::Display for MatchResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({})", self.decision, self.reason)
+    }
+}
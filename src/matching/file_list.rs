@@ -0,0 +1,59 @@
+use std::path::PathBuf;
+
+use lava_torrent::torrent::v1::Torrent;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct FileEntry {
+    /// Path of the file, including the name of the torrent as the root.
+    pub path: PathBuf,
+    /// Size of the file in bytes.
+    pub length: u64,
+}
+
+/// The files of a torrent, sorted by their path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileList {
+    pub name: String,
+    pub files: Vec<FileEntry>,
+}
+
+impl FileList {
+    pub fn new(name: String, mut files: Vec<FileEntry>) -> Self {
+        files.sort();
+
+        FileList { name, files }
+    }
+
+    /// The combined size of all the files in bytes.
+    pub fn total_size(&self) -> u64 {
+        self.files.iter().map(|f| f.length).sum()
+    }
+
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+}
+
+impl From<&Torrent> for FileList {
+    fn from(torrent: &Torrent) -> Self {
+        let files = match &torrent.files {
+            Some(files) => files.iter()
+                .map(|file| FileEntry {
+                    path: PathBuf::from(&torrent.name).join(&file.path),
+                    length: file.length as u64,
+                })
+                .collect(),
+            // Single file torrents use the name as the file name.
+            None => vec![FileEntry {
+                path: PathBuf::from(&torrent.name),
+                length: torrent.length as u64,
+            }],
+        };
+
+        FileList::new(torrent.name.clone(), files)
+    }
+}
@@ -0,0 +1,254 @@
+use std::collections::HashSet;
+use std::fmt;
+
+use bytes::Bytes;
+use lava_torrent::LavaTorrentError;
+use lava_torrent::torrent::v1::Torrent;
+
+use crate::torznab::{TorznabClient, TorrentResult, ClientError};
+
+use super::{FileList, FileEntry, MatchResult, MatchDecision, MatchReason};
+
+/// How far the size reported by the indexer may be from the local size, as a
+/// fraction, before the result is rejected without downloading it.
+const SIZE_TOLERANCE: f64 = 0.02;
+
+#[derive(Debug)]
+pub enum MatchError {
+    Download(ClientError),
+    InvalidTorrent(LavaTorrentError),
+}
+
+impl fmt::Display for MatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MatchError::Download(e) => write!(f, "failed to download the torrent: {}", e),
+            MatchError::InvalidTorrent(e) => write!(f, "invalid torrent: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for MatchError {}
+
+impl From<ClientError> for MatchError {
+    fn from(e: ClientError) -> Self {
+        MatchError::Download(e)
+    }
+}
+
+impl From<LavaTorrentError> for MatchError {
+    fn from(e: LavaTorrentError) -> Self {
+        MatchError::InvalidTorrent(e)
+    }
+}
+
+/// A search result together with its downloaded .torrent file.
+#[derive(Debug, Clone)]
+pub struct Candidate {
+    pub result: TorrentResult,
+    pub torrent: Torrent,
+    /// The raw .torrent file.
+    pub bytes: Bytes,
+}
+
+impl Candidate {
+    /// Download and parse the .torrent file of a search result.
+    pub async fn download(client: &TorznabClient, result: TorrentResult) -> Result<Candidate, MatchError> {
+        let bytes = client.download(&result.link).await?;
+        let torrent = Torrent::read_from_bytes(&bytes)?;
+
+        Ok(Candidate {
+            result,
+            torrent,
+            bytes,
+        })
+    }
+
+    pub fn file_list(&self) -> FileList {
+        FileList::from(&self.torrent)
+    }
+}
+
+/// Try to reject a search result using only the metadata from the indexer.
+///
+/// Returns `None` if the result needs to be downloaded to decide.
+pub fn pre_match(local: &FileList, local_hash: Option<&str>, result: &TorrentResult) -> Option<MatchResult> {
+    if let (Some(local_hash), Some(hash)) = (local_hash, &result.attributes.infohash) {
+        if local_hash.eq_ignore_ascii_case(hash) {
+            return Some(MatchResult::mismatch(MatchReason::SameInfoHash));
+        }
+    }
+
+    if let Some(size) = result.size {
+        let local_size = local.total_size();
+        let difference = (local_size as f64 - size as f64).abs();
+
+        if difference > local_size as f64 * SIZE_TOLERANCE {
+            return Some(MatchResult::mismatch(MatchReason::SizeDiffers {
+                local: local_size,
+                candidate: size,
+            }));
+        }
+    }
+
+    None
+}
+
+/// Compare the files of a candidate against the local files.
+pub fn match_files(local: &FileList, candidate: &FileList) -> MatchResult {
+    let local_files: HashSet<&FileEntry> = local.files.iter().collect();
+    let matched = candidate.files.iter()
+        .filter(|file| local_files.contains(file))
+        .count();
+
+    if matched == candidate.len() && local.len() == candidate.len() {
+        return MatchResult::new(MatchDecision::Match, MatchReason::IdenticalFiles {
+            files: matched,
+        });
+    }
+
+    let mut local_sizes: Vec<u64> = local.files.iter().map(|f| f.length).collect();
+    let mut candidate_sizes: Vec<u64> = candidate.files.iter().map(|f| f.length).collect();
+    local_sizes.sort_unstable();
+    candidate_sizes.sort_unstable();
+
+    if local_sizes == candidate_sizes {
+        MatchResult::new(MatchDecision::SizeOnly, MatchReason::DifferentPaths {
+            differing: candidate.len() - matched,
+        })
+    } else if matched > 0 {
+        MatchResult::new(MatchDecision::Partial, MatchReason::SomeFilesMatch {
+            matched,
+            total: candidate.len(),
+        })
+    } else {
+        MatchResult::mismatch(MatchReason::NoFilesMatch)
+    }
+}
+
+/// Decide if a search result is the same content as the local files,
+/// downloading its .torrent file if the metadata isn't enough.
+///
+/// The candidate is returned if it was downloaded.
+pub async fn evaluate(
+    client: &TorznabClient,
+    local: &FileList,
+    local_hash: Option<&str>,
+    result: TorrentResult,
+) -> Result<(MatchResult, Option<Candidate>), MatchError> {
+    if let Some(decision) = pre_match(local, local_hash, &result) {
+        return Ok((decision, None));
+    }
+
+    let candidate = Candidate::download(client, result).await?;
+
+    if let Some(local_hash) = local_hash {
+        if candidate.torrent.info_hash().eq_ignore_ascii_case(local_hash) {
+            return Ok((MatchResult::mismatch(MatchReason::SameInfoHash), Some(candidate)));
+        }
+    }
+
+    let decision = match_files(local, &candidate.file_list());
+
+    Ok((decision, Some(candidate)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::torznab::TorznabAttributes;
+
+    fn files(files: &[(&str, u64)]) -> FileList {
+        let files = files.iter()
+            .map(|(path, length)| FileEntry { path: path.into(), length: *length })
+            .collect();
+
+        FileList::new("Release".to_string(), files)
+    }
+
+    fn result(size: Option<u64>, infohash: Option<&str>) -> TorrentResult {
+        TorrentResult {
+            name: "Release".to_string(),
+            link: "http://indexer/download/1.torrent".to_string(),
+            size,
+            categories: Vec::new(),
+            pub_date: None,
+            guid: None,
+            comments: None,
+            enclosure: None,
+            attributes: TorznabAttributes {
+                infohash: infohash.map(str::to_string),
+                ..TorznabAttributes::default()
+            },
+        }
+    }
+
+    #[test]
+    fn matches_files() {
+        let local = files(&[("Release/a.mkv", 1_000), ("Release/b.nfo", 10)]);
+
+        let cases = [
+            (
+                files(&[("Release/a.mkv", 1_000), ("Release/b.nfo", 10)]),
+                MatchResult::new(MatchDecision::Match, MatchReason::IdenticalFiles { files: 2 }),
+            ),
+            (
+                files(&[("Release.Renamed/a.mkv", 1_000), ("Release/b.nfo", 10)]),
+                MatchResult::new(MatchDecision::SizeOnly, MatchReason::DifferentPaths { differing: 1 }),
+            ),
+            (
+                files(&[("Release/a.mkv", 1_000)]),
+                MatchResult::new(MatchDecision::Partial, MatchReason::SomeFilesMatch { matched: 1, total: 1 }),
+            ),
+            (
+                files(&[("Release/a.mkv", 1_000), ("Release/b.nfo", 10), ("Release/c.srt", 5)]),
+                MatchResult::new(MatchDecision::Partial, MatchReason::SomeFilesMatch { matched: 2, total: 3 }),
+            ),
+            (
+                files(&[("Release/a.mkv", 1_001), ("Release/b.nfo", 10)]),
+                MatchResult::new(MatchDecision::Partial, MatchReason::SomeFilesMatch { matched: 1, total: 2 }),
+            ),
+            (
+                files(&[("Other/c.mkv", 2_000)]),
+                MatchResult::mismatch(MatchReason::NoFilesMatch),
+            ),
+        ];
+
+        for (candidate, expected) in cases {
+            assert_eq!(match_files(&local, &candidate), expected, "{:?}", candidate);
+        }
+    }
+
+    #[test]
+    fn rejects_results_by_size_and_info_hash() {
+        let local = files(&[("Release/a.mkv", 10_000)]);
+        let size_differs = |candidate| Some(MatchResult::mismatch(MatchReason::SizeDiffers { local: 10_000, candidate }));
+
+        let cases = [
+            (result(None, None), None),
+            (result(Some(10_000), None), None),
+            // Up to 2% in both directions.
+            (result(Some(10_200), None), None),
+            (result(Some(10_201), None), size_differs(10_201)),
+            (result(Some(9_800), None), None),
+            (result(Some(9_799), None), size_differs(9_799)),
+            (result(None, Some("ABCDEF")), Some(MatchResult::mismatch(MatchReason::SameInfoHash))),
+            (result(None, Some("012345")), None),
+        ];
+
+        for (result, expected) in cases {
+            assert_eq!(pre_match(&local, Some("abcdef"), &result), expected, "{:?}", result.size);
+        }
+    }
+
+    #[test]
+    fn empty_local_files_only_match_empty_results() {
+        let local = files(&[("Release/empty", 0)]);
+
+        assert_eq!(pre_match(&local, None, &result(Some(0), None)), None);
+        assert_eq!(
+            pre_match(&local, None, &result(Some(1), None)),
+            Some(MatchResult::mismatch(MatchReason::SizeDiffers { local: 0, candidate: 1 })),
+        );
+    }
+}
@@ -0,0 +1,8 @@
+pub mod file_list;
+pub use file_list::*;
+
+pub mod decision;
+pub use decision::*;
+
+pub mod matcher;
+pub use matcher::*;
@@ -0,0 +1,5 @@
+pub mod template;
+pub use template::*;
+
+pub mod writer;
+pub use writer::*;
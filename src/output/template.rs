@@ -0,0 +1,245 @@
+use std::fmt;
+use std::str::FromStr;
+
+/// The template used when no template is configured.
+pub const DEFAULT_OUTPUT_TEMPLATE: &str = "[{tracker}] {name}.torrent";
+
+/// Most filesystems limit file names to 255 bytes.
+const MAX_FILE_NAME_LEN: usize = 255;
+
+/// The highest number appended to a file name that is already taken, like `Name (999).torrent`.
+pub const MAX_NAME_COLLISIONS: u32 = 999;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TemplateError {
+    UnknownPlaceholder(String),
+    UnclosedPlaceholder,
+}
+
+impl fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TemplateError::UnknownPlaceholder(name) => write!(f, "unknown placeholder {{{}}}", name),
+            TemplateError::UnclosedPlaceholder => write!(f, "placeholder is missing a closing }}"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Placeholder {
+    /// The name of the indexer the torrent was found on.
+    Tracker,
+    /// The name of the torrent.
+    Name,
+    /// The info hash of the torrent.
+    InfoHash,
+    /// The first category id of the search result.
+    Category,
+}
+
+impl FromStr for Placeholder {
+    type Err = TemplateError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "tracker" => Ok(Placeholder::Tracker),
+            "name" => Ok(Placeholder::Name),
+            "infohash" => Ok(Placeholder::InfoHash),
+            "category" => Ok(Placeholder::Category),
+            _ => Err(TemplateError::UnknownPlaceholder(s.to_string())),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum TemplatePart {
+    Text(String),
+    Placeholder(Placeholder),
+}
+
+/// The values that are substituted into a template.
+#[derive(Debug, Clone)]
+pub struct TemplateValues<'a> {
+    pub tracker: &'a str,
+    pub name: &'a str,
+    pub info_hash: &'a str,
+    pub category: Option<u32>,
+}
+
+/// A file name template, like `[{tracker}] {name}.torrent`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutputTemplate {
+    parts: Vec<TemplatePart>,
+}
+
+impl OutputTemplate {
+    /// Render the template into a file name that is safe to use on any
+    /// filesystem. The `.torrent` extension is added if it's missing.
+    pub fn render(&self, values: &TemplateValues) -> String {
+        let mut name = String::new();
+
+        for part in self.parts.iter() {
+            match part {
+                TemplatePart::Text(text) => name.push_str(text),
+                TemplatePart::Placeholder(placeholder) => {
+                    let value = match placeholder {
+                        Placeholder::Tracker => values.tracker.to_string(),
+                        Placeholder::Name => values.name.to_string(),
+                        Placeholder::InfoHash => values.info_hash.to_string(),
+                        Placeholder::Category => values.category
+                            .map(|c| c.to_string())
+                            .unwrap_or_default(),
+                    };
+
+                    name.push_str(&value);
+                }
+            }
+        }
+
+        let name = name.strip_suffix(".torrent").unwrap_or(&name);
+        let mut name = sanitize_file_name(name);
+
+        // Leave room for the extension and the widest collision suffix.
+        let suffix_len = format!(" ({})", MAX_NAME_COLLISIONS).len();
+        truncate_to_char_boundary(&mut name, MAX_FILE_NAME_LEN - ".torrent".len() - suffix_len);
+
+        format!("{}.torrent", name.trim_end())
+    }
+}
+
+impl Default for OutputTemplate {
+    fn default() -> Self {
+        DEFAULT_OUTPUT_TEMPLATE.parse().unwrap()
+    }
+}
+
+impl FromStr for OutputTemplate {
+    type Err = TemplateError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = Vec::new();
+        let mut rest = s;
+
+        while let Some(start) = rest.find('{') {
+            if start > 0 {
+                parts.push(TemplatePart::Text(rest[..start].to_string()));
+            }
+
+            let end = rest[start..].find('}').ok_or(TemplateError::UnclosedPlaceholder)? + start;
+            parts.push(TemplatePart::Placeholder(rest[start + 1..end].parse()?));
+
+            rest = &rest[end + 1..];
+        }
+
+        if !rest.is_empty() {
+            parts.push(TemplatePart::Text(rest.to_string()));
+        }
+
+        Ok(OutputTemplate { parts })
+    }
+}
+
+/// Replace characters that aren't allowed in file names on common filesystems.
+pub fn sanitize_file_name(name: &str) -> String {
+    let sanitized: String = name.chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect();
+
+    // Windows doesn't allow names ending with a dot or space.
+    let sanitized = sanitized.trim().trim_end_matches('.');
+
+    if sanitized.is_empty() {
+        "_".to_string()
+    } else {
+        sanitized.to_string()
+    }
+}
+
+fn truncate_to_char_boundary(s: &mut String, max_len: usize) {
+    if s.len() <= max_len {
+        return;
+    }
+
+    let mut len = max_len;
+    while !s.is_char_boundary(len) {
+        len -= 1;
+    }
+
+    s.truncate(len);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values(name: &str) -> TemplateValues<'_> {
+        TemplateValues {
+            tracker: "Tracker",
+            name,
+            info_hash: "0123456789abcdef0123456789abcdef01234567",
+            category: Some(2040),
+        }
+    }
+
+    #[test]
+    fn renders_placeholders() {
+        let cases = [
+            (DEFAULT_OUTPUT_TEMPLATE, "[Tracker] Movie.Name.2019.torrent"),
+            ("{name}", "Movie.Name.2019.torrent"),
+            ("{category}/{infohash}.torrent", "2040_0123456789abcdef0123456789abcdef01234567.torrent"),
+            ("{tracker}-{name}.torrent.torrent", "Tracker-Movie.Name.2019.torrent.torrent"),
+        ];
+
+        for (template, expected) in cases {
+            let template: OutputTemplate = template.parse().unwrap();
+            assert_eq!(template.render(&values("Movie.Name.2019")), expected, "{:?}", template);
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_templates() {
+        let cases = [
+            ("{title}.torrent", TemplateError::UnknownPlaceholder("title".to_string())),
+            ("{name.torrent", TemplateError::UnclosedPlaceholder),
+        ];
+
+        for (template, expected) in cases {
+            assert_eq!(template.parse::<OutputTemplate>(), Err(expected), "{}", template);
+        }
+    }
+
+    #[test]
+    fn sanitizes_file_names() {
+        let cases = [
+            ("Name: Part 1/2", "Name_ Part 1_2"),
+            ("What?*<>|\"\\", "What_______"),
+            ("Tab\there", "Tab_here"),
+            (" Name. ", "Name"),
+            ("...", "_"),
+            ("", "_"),
+        ];
+
+        for (name, expected) in cases {
+            assert_eq!(sanitize_file_name(name), expected, "{}", name);
+        }
+    }
+
+    #[test]
+    fn truncates_long_names_with_room_for_a_suffix() {
+        let template: OutputTemplate = "{name}".parse().unwrap();
+        let widest_suffix = format!(" ({})", MAX_NAME_COLLISIONS);
+
+        for name in ["a".repeat(300), "é".repeat(200), format!("{}.", "a".repeat(300))] {
+            let file_name = template.render(&values(&name));
+            let stem = file_name.strip_suffix(".torrent").unwrap();
+
+            assert!(name.starts_with(stem), "{}", file_name);
+            assert!(format!("{}{}.torrent", stem, widest_suffix).len() <= MAX_FILE_NAME_LEN, "{}", file_name);
+            assert!(file_name.len() > MAX_FILE_NAME_LEN - ".torrent".len() - widest_suffix.len() - "é".len(), "{}", file_name);
+        }
+    }
+}
@@ -0,0 +1,101 @@
+use std::io::{Error, ErrorKind};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use tokio::sync::Mutex;
+
+use crate::matching::Candidate;
+
+use super::{OutputTemplate, TemplateValues, MAX_NAME_COLLISIONS};
+
+/// Makes the names of temporary files unique within the process.
+static TEMP_FILE_ID: AtomicU64 = AtomicU64::new(0);
+
+/// The outcome of saving a torrent.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SaveOutcome {
+    Saved(PathBuf),
+    /// The same .torrent file was already saved at the path.
+    AlreadyExists(PathBuf),
+}
+
+/// Writes matched torrents into the output directory.
+#[derive(Debug, Clone)]
+pub struct TorrentWriter {
+    dir: PathBuf,
+    template: OutputTemplate,
+    /// Held while a file is moved into place, so saves don't take the same name.
+    rename_lock: Arc<Mutex<()>>,
+}
+
+impl TorrentWriter {
+    pub fn new(dir: &Path, template: OutputTemplate) -> Self {
+        TorrentWriter {
+            dir: dir.to_path_buf(),
+            template,
+            rename_lock: Arc::new(Mutex::new(())),
+        }
+    }
+
+    /// Save the .torrent file of a candidate that was found on `tracker`.
+    ///
+    /// If a different file already has the name, a number is appended to it.
+    /// The file is written under a temporary name first, so a failed write
+    /// never leaves a truncated .torrent file for watch folders to pick up.
+    pub async fn save(&self, tracker: &str, candidate: &Candidate) -> std::io::Result<SaveOutcome> {
+        let info_hash = candidate.torrent.info_hash();
+        let file_name = self.template.render(&TemplateValues {
+            tracker,
+            name: &candidate.torrent.name,
+            info_hash: &info_hash,
+            category: candidate.result.categories.first().copied(),
+        });
+
+        tokio::fs::create_dir_all(&self.dir).await?;
+
+        let temp_id = TEMP_FILE_ID.fetch_add(1, Ordering::Relaxed);
+        let temp_path = self.dir.join(format!(".{}.{}.{}.part", info_hash, std::process::id(), temp_id));
+
+        let outcome = match tokio::fs::write(&temp_path, &candidate.bytes).await {
+            Ok(()) => self.move_into_place(&temp_path, &file_name, &candidate.bytes).await,
+            Err(e) => Err(e),
+        };
+
+        if !matches!(outcome, Ok(SaveOutcome::Saved(_))) {
+            let _ = tokio::fs::remove_file(&temp_path).await;
+        }
+
+        outcome
+    }
+
+    /// Rename the temporary file to the first free name, unless a file with the same bytes exists.
+    async fn move_into_place(&self, temp_path: &Path, file_name: &str, bytes: &[u8]) -> std::io::Result<SaveOutcome> {
+        // Renaming replaces existing files, so finding a free name and taking
+        // it can't be interleaved with other saves.
+        let _guard = self.rename_lock.lock().await;
+
+        let stem = file_name.strip_suffix(".torrent").unwrap_or(file_name);
+        for attempt in 0..=MAX_NAME_COLLISIONS {
+            let path = match attempt {
+                0 => self.dir.join(file_name),
+                n => self.dir.join(format!("{} ({}).torrent", stem, n)),
+            };
+
+            match tokio::fs::read(&path).await {
+                Ok(existing) if existing == bytes => return Ok(SaveOutcome::AlreadyExists(path)),
+                Ok(_) => {},
+                Err(e) if e.kind() == ErrorKind::NotFound => {
+                    tokio::fs::rename(temp_path, &path).await?;
+                    return Ok(SaveOutcome::Saved(path));
+                },
+                Err(e) => return Err(e),
+            }
+        }
+
+        Err(Error::new(
+            ErrorKind::AlreadyExists,
+            format!("{} and {} numbered copies of it are taken by other files", file_name, MAX_NAME_COLLISIONS),
+        ))
+    }
+}
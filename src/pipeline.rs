@@ -0,0 +1,392 @@
+//! A cross-seed run: search the indexers for every torrent and save the matches.
+
+use std::collections::HashSet;
+use std::error::Error;
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use futures::StreamExt;
+use tokio::sync::RwLock;
+use tracing::{info, warn, debug};
+
+use crate::clients::{self, InjectOutcome, TorrentClient};
+use crate::config::{Config, Indexer};
+use crate::matching::{self, Candidate, MatchDecision, MatchResult};
+use crate::output::{TorrentWriter, SaveOutcome, TemplateError};
+use crate::redact::redact;
+use crate::release::{SearchPlan, IdMap};
+use crate::scheduler::Scheduler;
+use crate::source::{ClientSource, DataDirs, SourceError, TorrentDir, TorrentSource};
+use crate::state::StateDb;
+use crate::torznab::ClientError;
+use crate::torznab::search_parameters::GenericSearchParametersBuilder;
+
+/// A torrent that was found on an indexer.
+#[derive(Debug, Clone)]
+pub struct FoundMatch {
+    /// The name of the local torrent.
+    pub torrent: String,
+    pub indexer: String,
+    pub result: MatchResult,
+    pub candidate: Candidate,
+    /// Where the .torrent file was saved, if it was saved by this run.
+    pub saved: Option<PathBuf>,
+    /// The torrent clients the cross-seed was added to by this run.
+    pub injected: Vec<String>,
+}
+
+/// What happened during a run.
+#[derive(Debug, Default)]
+pub struct RunSummary {
+    pub matches: Vec<FoundMatch>,
+    /// Indexers that were skipped since their capabilities couldn't be fetched.
+    pub failed_indexers: Vec<String>,
+    /// Indexers that were disabled during the run, with the reason.
+    pub disabled_indexers: Vec<(String, String)>,
+}
+
+/// Errors that stop a run before any search.
+#[derive(Debug)]
+pub enum RunError {
+    /// None of `torrents_path`, `source_client` and `data_paths` is configured.
+    NoSource,
+    /// `source_client` isn't one of the configured clients.
+    UnknownClient(String),
+    Source(SourceError),
+    OutputTemplate(TemplateError),
+    State(rusqlite::Error),
+    IdMap(String),
+}
+
+impl fmt::Display for RunError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RunError::NoSource => write!(f, "one of torrents_path, source_client or data_paths must be set"),
+            RunError::UnknownClient(name) => write!(f, "there is no client named {}", name),
+            RunError::Source(e) => write!(f, "failed to read the torrents: {}", e),
+            RunError::OutputTemplate(e) => write!(f, "invalid output template: {}", e),
+            RunError::State(e) => write!(f, "failed to open the state database: {}", e),
+            RunError::IdMap(e) => write!(f, "failed to read the id map: {}", e),
+        }
+    }
+}
+
+impl Error for RunError {}
+
+/// Create a source for each of `source_client`, `torrents_path` and `data_paths` that is configured.
+pub fn create_sources(config: &Config) -> Result<Vec<Box<dyn TorrentSource>>, RunError> {
+    let mut sources: Vec<Box<dyn TorrentSource>> = Vec::new();
+    if let Some(name) = config.source_client() {
+        let client = config.client(name).ok_or_else(|| RunError::UnknownClient(name.to_string()))?;
+        sources.push(Box::new(ClientSource::new(client.create_client())));
+    }
+
+    if let Some(path) = config.torrents_path() {
+        sources.push(Box::new(TorrentDir::new(path)));
+    }
+
+    let data_paths: Vec<PathBuf> = config.data_paths().map(PathBuf::from).collect();
+    if !data_paths.is_empty() {
+        sources.push(Box::new(DataDirs::new(data_paths)));
+    }
+
+    if sources.is_empty() {
+        return Err(RunError::NoSource);
+    }
+
+    Ok(sources)
+}
+
+/// Collect the configured and discovered indexers and create their clients.
+///
+/// Returns the indexers with a client and the names of the ones that failed.
+pub async fn prepare_indexers(config: &Config) -> (Vec<Indexer>, Vec<String>) {
+    let mut indexers = config.indexers.clone();
+
+    // Add the indexers of Jackett and Prowlarr, unless they are configured by hand.
+    for discovery in config.discovery() {
+        match discovery.indexers().await {
+            Ok(discovered) => {
+                info!("Discovered {} indexers on {}", discovered.len(), discovery.name);
+                for indexer in discovered {
+                    if !indexers.iter().any(|i| i.name == indexer.name) {
+                        indexers.push(indexer);
+                    }
+                }
+            },
+            Err(e) => warn!("Failed to discover the indexers of {}: {}", discovery.name, e),
+        }
+    }
+
+    // Create torznab clients for each indexer, skipping the ones that fail.
+    let mut failed_indexers = Vec::new();
+    for indexer in indexers.iter_mut() {
+        if let Err(e) = indexer.create_client().await {
+            warn!("Failed to get the capabilities of {}: {}", indexer.name, e);
+            failed_indexers.push(indexer.name.clone());
+        }
+    }
+    indexers.retain(|indexer| indexer.client.is_some());
+
+    (indexers, failed_indexers)
+}
+
+/// Search every torrent on every indexer, saving the matches when there is an output path.
+pub async fn run(config: &Config) -> Result<RunSummary, RunError> {
+    let sources = create_sources(config)?;
+    for source in sources.iter() {
+        info!("Searching torrents in: {}", source.describe());
+    }
+
+    // Matched torrents are only saved if there is an output path.
+    let writer = match config.output_path() {
+        Some(path) => {
+            let template = config.output_template().map_err(RunError::OutputTemplate)?;
+            info!("Saving matched torrents to: {}", path.display());
+
+            Some(TorrentWriter::new(path, template))
+        },
+        None => None,
+    };
+    let writer = Arc::new(writer);
+
+    let state = StateDb::open(config.state_path()).map_err(RunError::State)?;
+    let state = Arc::new(state);
+    let research_interval = config.research_interval();
+    let max_search_results = config.max_search_results();
+
+    // Ids for torrents that don't contain them.
+    let id_map = match config.id_map_path() {
+        Some(path) => Some(IdMap::read_from_file(path).map_err(RunError::IdMap)?),
+        None => None,
+    };
+
+    let (indexers, failed_indexers) = prepare_indexers(config).await;
+
+    let torrent_clients: Vec<Box<dyn TorrentClient>> = config.clients()
+        .map(|client| client.create_client())
+        .collect();
+    let torrent_clients = Arc::new(torrent_clients);
+
+    // Log the trackers
+    info!("Searching {} trackers: ", indexers.len());
+    for indexer in indexers.iter() {
+        let client = indexer.client.as_ref().unwrap();
+        match client.capabilities.server.as_ref().and_then(|server| server.identity()) {
+            Some(identity) => info!("  {}: {} ({})", indexer.name, redact(&indexer.url), identity),
+            None => info!("  {}: {}", indexer.name, redact(&indexer.url)),
+        }
+        debug!("    Can Search: {:?}", client.capabilities.searching_capabilities);
+        debug!("    Limits: {:?}", client.capabilities.limits);
+    }
+
+    // A torrent can be in more than one source, like a client and its .torrent
+    // directory. Only the first source that has it is used.
+    let mut keys = HashSet::new();
+    let mut items = Vec::new();
+    for source in sources.iter() {
+        let found = source.items().await.map_err(RunError::Source)?;
+        items.extend(found.into_iter().filter(|item| keys.insert(item.state_key())));
+    }
+    info!("Found {} torrents", items.len());
+
+    // Limit the amount of searches running at the same time.
+    let mut scheduler = Scheduler::new(config.max_concurrency());
+    for indexer in indexers.iter() {
+        scheduler.add_indexer(&indexer.name, indexer.max_concurrency());
+    }
+
+    // Convert the indexers to be async friendly.
+    let indexers = indexers.iter()
+        .map(|indexer| (indexer.name.clone(), Arc::new(RwLock::new(indexer.clone()))))
+        .collect::<Vec<_>>();
+
+    let mut searches = Vec::new();
+
+    for item in items {
+        let release = Arc::new(item.release(id_map.as_ref()));
+        // Data without a torrent is remembered by its path.
+        let key = Arc::new(item.state_key());
+        let torrent = Arc::new(item.name);
+        let files = Arc::new(item.files);
+        let info_hash = Arc::new(item.info_hash);
+        let save_path = Arc::new(item.save_path);
+        info!("{}:", torrent);
+
+        for (indexer_name, indexer) in indexers.iter() {
+            // Skip indexers that were searched recently.
+            match state.is_search_fresh(&key, indexer_name, research_interval) {
+                Ok(true) => {
+                    debug!("Skipping {} on {}, it was searched recently", torrent, indexer_name);
+                    continue;
+                },
+                Ok(false) => {},
+                Err(e) => warn!("Failed to read the state of {}: {}", torrent, e),
+            }
+
+            let indexer = Arc::clone(indexer);
+            let torrent = Arc::clone(&torrent);
+            let files = Arc::clone(&files);
+            let key = Arc::clone(&key);
+            let info_hash = Arc::clone(&info_hash);
+            let save_path = Arc::clone(&save_path);
+            let release = Arc::clone(&release);
+            let writer = Arc::clone(&writer);
+            let state = Arc::clone(&state);
+            let torrent_clients = Arc::clone(&torrent_clients);
+            searches.push((indexer_name.clone(), async move {
+                let lock = indexer.read().await;
+                let client = lock.client.as_ref().expect("indexers without a client are skipped");
+
+                let plan = SearchPlan::new(&release, &client.capabilities.searching_capabilities);
+                debug!("Searching {} on {} with {:?}", torrent, lock.name, plan);
+
+                let mut generic = GenericSearchParametersBuilder::new();
+                if let Some(query) = plan.query {
+                    generic = generic.query(query);
+                }
+                let generic = generic.build();
+                let results = client.search_paginated(plan.function, generic, max_search_results);
+                futures::pin_mut!(results);
+
+                // Compare each result against the local torrent as the pages come in.
+                let mut matches = Vec::new();
+                let mut result_count = 0;
+                while let Some(result) = results.next().await {
+                    let result = match result {
+                        Ok(result) => result,
+                        // Already summarized at the end of the run.
+                        Err(ClientError::IndexerDisabled(_)) => return matches,
+                        Err(e) => {
+                            warn!("Failed to search {} on {}: {}", torrent, lock.name, e);
+                            return matches;
+                        }
+                    };
+                    result_count += 1;
+
+                    // Results that didn't match earlier won't match now.
+                    if let Ok(Some(decision)) = state.decision(&key, &lock.name, &result) {
+                        if !decision.is_match() {
+                            debug!("{} on {}: {} (earlier run)", result.name, lock.name, decision);
+                            continue;
+                        }
+                    }
+
+                    let name = result.name.clone();
+                    let record = result.clone();
+                    match matching::evaluate(client, &files, info_hash.as_deref(), result).await {
+                        Ok((decision, candidate)) => {
+                            debug!("{} on {}: {}", name, lock.name, decision);
+
+                            if let Err(e) = state.record_decision(&key, &lock.name, &record, &decision) {
+                                warn!("Failed to store the decision for {}: {}", name, e);
+                            }
+
+                            if let (true, Some(candidate)) = (decision.decision.is_match(), candidate) {
+                                info!("Found {} for {} on {}: {}", decision, torrent, lock.name, name);
+                                if !candidate.result.attributes.tags.is_empty() {
+                                    info!("  Tags: {}", candidate.result.attributes.tags.join(", "));
+                                }
+
+                                let mut saved = None;
+                                if let Some(writer) = writer.as_ref() {
+                                    match writer.save(&lock.name, &candidate).await {
+                                        Ok(SaveOutcome::Saved(path)) => {
+                                            info!("Saved {}", path.display());
+                                            saved = Some(path);
+                                        },
+                                        Ok(SaveOutcome::AlreadyExists(path)) => debug!("Already saved {}", path.display()),
+                                        Err(e) => warn!("Failed to save {}: {}", name, e),
+                                    }
+                                }
+
+                                // Only identical files can be seeded from the same save path.
+                                let mut injected = Vec::new();
+                                if decision.decision == MatchDecision::Match {
+                                    for client in torrent_clients.iter() {
+                                        if inject(client.as_ref(), info_hash.as_deref(), save_path.as_deref(), &candidate).await {
+                                            injected.push(client.name().to_string());
+                                        }
+                                    }
+                                }
+
+                                matches.push(FoundMatch {
+                                    torrent: torrent.to_string(),
+                                    indexer: lock.name.clone(),
+                                    result: decision,
+                                    candidate,
+                                    saved,
+                                    injected,
+                                });
+                            }
+                        },
+                        Err(e) => warn!("Failed to check {} on {}: {}", name, lock.name, e),
+                    }
+                }
+
+                info!("Found {} results for {} on {}", result_count, torrent, lock.name);
+
+                if let Err(e) = state.record_search(&key, &lock.name) {
+                    warn!("Failed to store the search of {}: {}", torrent, e);
+                }
+
+                matches
+            }));
+        }
+    }
+
+    let indexer_handles = scheduler.spawn_all(searches);
+
+    let mut summary = RunSummary {
+        failed_indexers,
+        ..RunSummary::default()
+    };
+
+    for handle in futures::future::join_all(indexer_handles).await {
+        match handle {
+            Ok(mut matches) => summary.matches.append(&mut matches),
+            Err(e) => warn!("A search failed: {}", e),
+        }
+    }
+
+    for (name, indexer) in indexers.iter() {
+        let lock = indexer.read().await;
+        if let Some(reason) = lock.client.as_ref().and_then(|client| client.disabled_reason()) {
+            summary.disabled_indexers.push((name.clone(), reason));
+        }
+    }
+
+    Ok(summary)
+}
+
+/// Add a cross-seed to a torrent client, returning true if it was added.
+///
+/// It's added next to the original torrent, or in the save path of data without a torrent.
+async fn inject(client: &dyn TorrentClient, original_hash: Option<&str>, save_path: Option<&str>, candidate: &Candidate) -> bool {
+    let name = &candidate.torrent.name;
+    let outcome = match (original_hash, save_path) {
+        (Some(original_hash), _) => clients::inject(client, original_hash, candidate).await,
+        (None, Some(save_path)) => clients::inject_at(client, save_path, candidate).await,
+        (None, None) => return false,
+    };
+
+    match outcome {
+        Ok(InjectOutcome::Added(save_path)) => {
+            info!("Added {} to {} in {}", name, client.name(), save_path);
+            true
+        },
+        Ok(InjectOutcome::AlreadyExists) => {
+            debug!("{} already has {}", client.name(), name);
+            false
+        },
+        Ok(InjectOutcome::OriginalNotFound) => {
+            debug!("{} doesn't have the original of {}", client.name(), name);
+            false
+        },
+        Err(e) => {
+            warn!("Failed to add {} to {}: {}", name, client.name(), e);
+            false
+        },
+    }
+}
@@ -0,0 +1,41 @@
+use super::parse_credited;
+
+/// A book parsed from a release name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BookRelease {
+    pub author: String,
+    pub title: String,
+    pub year: Option<u16>,
+}
+
+impl BookRelease {
+    /// Parse a release name like `Author Name - Book Title (2019) [EPUB]`.
+    pub fn parse(name: &str) -> Option<Self> {
+        let (author, title, year) = parse_credited(name)?;
+
+        Some(BookRelease { author, title, year })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn book(author: &str, title: &str, year: Option<u16>) -> Option<BookRelease> {
+        Some(BookRelease { author: author.to_string(), title: title.to_string(), year })
+    }
+
+    #[test]
+    fn parses_books() {
+        let cases = [
+            ("Author Name - Book Title (2019) [EPUB]", book("Author Name", "Book Title", Some(2019))),
+            ("Author Name - Book Title.epub", book("Author Name", "Book Title", None)),
+            ("A. N. Author - Book Title [retail]", book("A N Author", "Book Title", None)),
+            ("Book.Title.2019.EPUB", None),
+        ];
+
+        for (name, expected) in cases {
+            assert_eq!(BookRelease::parse(name), expected, "{}", name);
+        }
+    }
+}
@@ -0,0 +1,100 @@
+use std::collections::HashMap;
+
+use crate::matching::FileList;
+
+const VIDEO_EXTENSIONS: &[&str] = &["mkv", "mp4", "avi", "m2ts", "ts", "wmv", "mov", "m4v", "vob", "iso"];
+const AUDIO_EXTENSIONS: &[&str] = &["flac", "mp3", "m4a", "aac", "ogg", "opus", "wav", "alac", "ape", "wv", "dsf", "m4b"];
+const BOOK_EXTENSIONS: &[&str] = &["epub", "mobi", "azw", "azw3", "pdf", "djvu", "cbz", "cbr", "fb2"];
+
+/// The kind of content in a torrent, detected from the extensions of its files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum ContentKind {
+    Video,
+    Audio,
+    Book,
+    #[default]
+    Other,
+}
+
+impl ContentKind {
+    pub fn from_extension(extension: &str) -> Self {
+        let extension = extension.to_ascii_lowercase();
+        if VIDEO_EXTENSIONS.contains(&extension.as_str()) {
+            ContentKind::Video
+        } else if AUDIO_EXTENSIONS.contains(&extension.as_str()) {
+            ContentKind::Audio
+        } else if BOOK_EXTENSIONS.contains(&extension.as_str()) {
+            ContentKind::Book
+        } else {
+            ContentKind::Other
+        }
+    }
+
+    /// The kind that makes up most of the size of the files. Covers, NFOs and
+    /// other small extras don't change the kind of a release.
+    pub fn detect(files: &FileList) -> Self {
+        let mut sizes: HashMap<ContentKind, u64> = HashMap::new();
+        for file in files.files.iter() {
+            let kind = file.path.extension()
+                .and_then(|extension| extension.to_str())
+                .map(ContentKind::from_extension)
+                .unwrap_or_default();
+
+            *sizes.entry(kind).or_default() += file.length;
+        }
+
+        sizes.into_iter()
+            .filter(|(kind, _)| *kind != ContentKind::Other)
+            .max_by_key(|(_, size)| *size)
+            .map(|(kind, _)| kind)
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::matching::FileEntry;
+
+    fn files(files: &[(&str, u64)]) -> FileList {
+        let files = files.iter()
+            .map(|(path, length)| FileEntry { path: path.into(), length: *length })
+            .collect();
+
+        FileList::new("Release".to_string(), files)
+    }
+
+    #[test]
+    fn kinds_of_extensions() {
+        let cases = [
+            ("mkv", ContentKind::Video),
+            ("MKV", ContentKind::Video),
+            ("flac", ContentKind::Audio),
+            ("m4b", ContentKind::Audio),
+            ("EPUB", ContentKind::Book),
+            ("cbz", ContentKind::Book),
+            ("nfo", ContentKind::Other),
+            ("", ContentKind::Other),
+        ];
+
+        for (extension, expected) in cases {
+            assert_eq!(ContentKind::from_extension(extension), expected, "{}", extension);
+        }
+    }
+
+    #[test]
+    fn detects_the_kind_with_the_most_data() {
+        let cases = [
+            (files(&[("Release/movie.mkv", 4_000), ("Release/movie.nfo", 1), ("Release/poster.jpg", 5_000)]), ContentKind::Video),
+            (files(&[("Release/01.flac", 300), ("Release/02.flac", 300), ("Release/booklet.pdf", 100)]), ContentKind::Audio),
+            (files(&[("Release/book.epub", 10), ("Release/cover.jpg", 500)]), ContentKind::Book),
+            (files(&[("Release/sample.mkv", 100), ("Release/01.flac", 300)]), ContentKind::Audio),
+            (files(&[("Release/readme.txt", 10), ("Release/data", 10)]), ContentKind::Other),
+            (files(&[]), ContentKind::Other),
+        ];
+
+        for (files, expected) in cases {
+            assert_eq!(ContentKind::detect(&files), expected, "{:?}", files);
+        }
+    }
+}
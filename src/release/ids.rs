@@ -0,0 +1,134 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::LazyLock;
+
+use lava_torrent::bencode::BencodeElem;
+use lava_torrent::torrent::v1::Torrent;
+use regex::Regex;
+use serde::Deserialize;
+
+use crate::torznab::search_parameters::ImdbId;
+
+static IMDB_ID: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\btt(\d{7,8})\b").unwrap());
+
+static TMDB_URL: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"themoviedb\.org/movie/(\d+)").unwrap()
+});
+
+/// Ids of the content on metadata sites.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct MediaIds {
+    #[serde(default, deserialize_with = "deserialize_imdb_id")]
+    pub imdb: Option<ImdbId>,
+    pub tmdb: Option<u32>,
+}
+
+impl MediaIds {
+    /// Find ids in free text, like a torrent comment or an NFO.
+    pub fn from_text(text: &str) -> Self {
+        MediaIds {
+            imdb: IMDB_ID.captures(text).and_then(|c| c[1].parse().ok()),
+            tmdb: TMDB_URL.captures(text).and_then(|c| c[1].parse().ok()),
+        }
+    }
+
+    /// Find ids in the text fields of a torrent, like the comment.
+    pub fn from_torrent(torrent: &Torrent) -> Self {
+        let mut ids = MediaIds::default();
+
+        let fields = torrent.extra_fields.iter().chain(torrent.extra_info_fields.iter())
+            .flat_map(|fields| fields.values());
+        for field in fields {
+            if let BencodeElem::String(text) = field {
+                ids.merge(MediaIds::from_text(text));
+            }
+        }
+
+        ids
+    }
+
+    /// Fill in the ids that are missing with the ids from `other`.
+    pub fn merge(&mut self, other: MediaIds) {
+        if self.imdb.is_none() {
+            self.imdb = other.imdb;
+        }
+
+        if self.tmdb.is_none() {
+            self.tmdb = other.tmdb;
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.imdb.is_none() && self.tmdb.is_none()
+    }
+}
+
+fn deserialize_imdb_id<'de, D>(deserializer: D) -> Result<Option<ImdbId>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let id: Option<String> = Deserialize::deserialize(deserializer)?;
+    id.map(|id| id.parse().map_err(serde::de::Error::custom)).transpose()
+}
+
+/// Ids for torrents that don't contain them, keyed by torrent name or info hash.
+///
+/// The map is a TOML file like:
+/// ```toml
+/// ["Movie.Name.2019.1080p.BluRay.x264-GROUP"]
+/// imdb = "tt1234567"
+/// tmdb = 12345
+/// ```
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(transparent)]
+pub struct IdMap {
+    ids: HashMap<String, MediaIds>,
+}
+
+impl IdMap {
+    pub fn read_from_file(path: &Path) -> Result<Self, String> {
+        let contents = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        toml::from_str(&contents).map_err(|e| e.to_string())
+    }
+
+    pub fn get(&self, name: &str, info_hash: Option<&str>) -> Option<&MediaIds> {
+        info_hash.and_then(|info_hash| self.ids.get(info_hash)).or_else(|| self.ids.get(name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ids(imdb: Option<&str>, tmdb: Option<u32>) -> MediaIds {
+        MediaIds { imdb: imdb.map(|id| id.parse().unwrap()), tmdb }
+    }
+
+    #[test]
+    fn finds_ids_in_text() {
+        let cases = [
+            ("https://www.imdb.com/title/tt0133093/", ids(Some("tt0133093"), None)),
+            ("IMDB: tt12345678", ids(Some("tt12345678"), None)),
+            ("https://www.themoviedb.org/movie/603-the-matrix", ids(None, Some(603))),
+            ("imdb.com/title/tt0133093 themoviedb.org/movie/603", ids(Some("tt0133093"), Some(603))),
+            // Too short, too long or part of a word.
+            ("tt123456", ids(None, None)),
+            ("tt123456789", ids(None, None)),
+            ("att0133093", ids(None, None)),
+            ("themoviedb.org/tv/1399", ids(None, None)),
+            ("", ids(None, None)),
+        ];
+
+        for (text, expected) in cases {
+            assert_eq!(MediaIds::from_text(text), expected, "{}", text);
+        }
+    }
+
+    #[test]
+    fn merge_keeps_existing_ids() {
+        let mut merged = ids(Some("tt0133093"), None);
+        merged.merge(ids(Some("tt0234215"), Some(603)));
+
+        assert_eq!(merged, ids(Some("tt0133093"), Some(603)));
+    }
+}
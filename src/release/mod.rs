@@ -0,0 +1,134 @@
+pub mod tv;
+pub use tv::*;
+
+pub mod movie;
+pub use movie::*;
+
+pub mod music;
+pub use music::*;
+
+pub mod book;
+pub use book::*;
+
+pub mod ids;
+pub use ids::*;
+
+pub mod content;
+pub use content::*;
+
+pub mod search;
+pub use search::*;
+
+use std::sync::LazyLock;
+
+use lava_torrent::torrent::v1::Torrent;
+use regex::Regex;
+
+use crate::matching::FileList;
+
+/// `Creator - Work (2019) [Format]`, the usual naming of music and books.
+static CREDITED: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^(?P<creator>.+?)\s+-\s+(?P<work>[^(\[]+?)\s*(?P<rest>[(\[].*)?$").unwrap()
+});
+
+/// A year on its own, like `(2019)` or `-2019-`.
+static YEAR: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?:^|[^0-9])(?P<year>(?:19|20)\d{2})(?:[^0-9]|$)").unwrap()
+});
+
+/// What is known about the content that is searched for.
+#[derive(Debug, Clone, Default)]
+pub struct ReleaseInfo {
+    pub name: String,
+    pub kind: ContentKind,
+    pub ids: MediaIds,
+}
+
+impl ReleaseInfo {
+    /// Collect the info of a torrent, using the id map for ids the torrent doesn't contain.
+    pub fn from_torrent(torrent: &Torrent, id_map: Option<&IdMap>) -> Self {
+        let files = FileList::from(torrent);
+        Self::from_files(&files, Some(&torrent.info_hash()), MediaIds::from_torrent(torrent), id_map)
+    }
+
+    /// Collect the info of files without a .torrent, like from a torrent client
+    /// or from data on disk, which has no info hash.
+    ///
+    /// The id map takes precedence over the known `ids`.
+    pub fn from_files(files: &FileList, info_hash: Option<&str>, ids: MediaIds, id_map: Option<&IdMap>) -> Self {
+        let mut mapped = id_map
+            .and_then(|map| map.get(&files.name, info_hash))
+            .cloned()
+            .unwrap_or_default();
+        mapped.merge(ids);
+
+        ReleaseInfo {
+            name: files.name.clone(),
+            kind: ContentKind::detect(files),
+            ids: mapped,
+        }
+    }
+}
+
+/// Turn the separators of a release name into spaces.
+pub fn clean_title(title: &str) -> String {
+    title.replace(['.', '_'], " ")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .trim_matches(|c: char| c == '-' || c.is_whitespace())
+        .to_string()
+}
+
+/// Split a `Creator - Work (2019) [Format]` name into the creator, the work and the year.
+fn parse_credited(name: &str) -> Option<(String, String, Option<u16>)> {
+    // Single file torrents are named after the file.
+    let name = match name.rsplit_once('.') {
+        Some((stem, extension)) if ContentKind::from_extension(extension) != ContentKind::Other => stem,
+        _ => name,
+    };
+
+    let captures = CREDITED.captures(name)?;
+
+    let creator = clean_title(&captures["creator"]);
+    let work = clean_title(&captures["work"]);
+    if creator.is_empty() || work.is_empty() {
+        return None;
+    }
+
+    let year = captures.name("rest")
+        .and_then(|rest| YEAR.captures(rest.as_str()))
+        .and_then(|captures| captures["year"].parse().ok());
+
+    Some((creator, work, year))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn credited(creator: &str, work: &str, year: Option<u16>) -> Option<(String, String, Option<u16>)> {
+        Some((creator.to_string(), work.to_string(), year))
+    }
+
+    #[test]
+    fn parses_credited_names() {
+        let cases = [
+            ("Artist - Album (2019) [FLAC]", credited("Artist", "Album", Some(2019))),
+            ("Artist Name - Album Name [2019] [FLAC 24-96]", credited("Artist Name", "Album Name", Some(2019))),
+            ("Artist - Album [FLAC]", credited("Artist", "Album", None)),
+            ("Artist - Album", credited("Artist", "Album", None)),
+            // Media extensions of single file torrents are removed, other ones are part of the name.
+            ("Author Name - Book Title (2019).epub", credited("Author Name", "Book Title", Some(2019))),
+            ("Artist - Song.FLAC", credited("Artist", "Song", None)),
+            ("Artist - Album.v2", credited("Artist", "Album v2", None)),
+            ("Movie.Name.2019.1080p.BluRay.x264-GRP", None),
+            ("Artist-Album", None),
+            ("Artist - (2019)", None),
+        ];
+
+        for (name, expected) in cases {
+            assert_eq!(parse_credited(name), expected, "{}", name);
+        }
+    }
+}
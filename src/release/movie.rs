@@ -0,0 +1,66 @@
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+use super::clean_title;
+
+/// `Movie.Name.2019.1080p` or `Movie Name (2019)`.
+static TITLE_YEAR: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^(?P<title>.+?)[ ._(\[-]+(?P<year>(?:19|20)\d{2})(?:[ ._)\]-]|$)").unwrap()
+});
+
+/// A movie parsed from a release name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MovieRelease {
+    pub title: String,
+    pub year: u16,
+}
+
+impl MovieRelease {
+    /// Parse a release name like `Movie.Name.2019.1080p.BluRay.x264-GROUP`.
+    pub fn parse(name: &str) -> Option<Self> {
+        let captures = TITLE_YEAR.captures(name)?;
+
+        let title = clean_title(&captures["title"]);
+        if title.is_empty() {
+            return None;
+        }
+
+        Some(MovieRelease {
+            title,
+            year: captures["year"].parse().ok()?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn movie(title: &str, year: u16) -> Option<MovieRelease> {
+        Some(MovieRelease { title: title.to_string(), year })
+    }
+
+    #[test]
+    fn parses_title_and_year() {
+        let cases = [
+            ("Movie.Name.2019.1080p.BluRay.x264-GRP", movie("Movie Name", 2019)),
+            ("Movie Name (2019) [1080p]", movie("Movie Name", 2019)),
+            ("Movie Name [1999]", movie("Movie Name", 1999)),
+            ("Movie_Name_2003_DVDRip", movie("Movie Name", 2003)),
+            ("Movie.Name.2019", movie("Movie Name", 2019)),
+            // The year is the first one after the title.
+            ("2001.A.Space.Odyssey.1968.1080p.BluRay", movie("2001 A Space Odyssey", 1968)),
+            ("1917.2019.2160p.UHD.BluRay", movie("1917", 2019)),
+            // No year.
+            ("Movie.Name.1080p.BluRay.x264-GRP", None),
+            ("Movie.Name.1899.1080p", None),
+            ("Movie.Name.20191080p", None),
+            ("2019", None),
+        ];
+
+        for (name, expected) in cases {
+            assert_eq!(MovieRelease::parse(name), expected, "{}", name);
+        }
+    }
+}
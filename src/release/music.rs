@@ -0,0 +1,68 @@
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+use super::{clean_title, parse_credited, YEAR};
+
+/// `Artist-Album-WEB-2019-GROUP`, the scene naming of music releases.
+static SCENE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^(?P<artist>[^-\s]+)-(?P<album>[^-\s]+)-(?P<rest>.+)$").unwrap()
+});
+
+/// An album parsed from a release name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MusicRelease {
+    pub artist: String,
+    pub album: String,
+    pub year: Option<u16>,
+}
+
+impl MusicRelease {
+    /// Parse a release name like `Artist - Album (2019) [FLAC]` or `Artist-Album-WEB-2019-GROUP`.
+    pub fn parse(name: &str) -> Option<Self> {
+        if let Some((artist, album, year)) = parse_credited(name) {
+            return Some(MusicRelease { artist, album, year });
+        }
+
+        let captures = SCENE.captures(name)?;
+        let artist = clean_title(&captures["artist"]);
+        let album = clean_title(&captures["album"]);
+        if artist.is_empty() || album.is_empty() {
+            return None;
+        }
+
+        Some(MusicRelease {
+            artist,
+            album,
+            year: YEAR.captures(&captures["rest"])
+                .and_then(|captures| captures["year"].parse().ok()),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn album(artist: &str, album: &str, year: Option<u16>) -> Option<MusicRelease> {
+        Some(MusicRelease { artist: artist.to_string(), album: album.to_string(), year })
+    }
+
+    #[test]
+    fn parses_albums() {
+        let cases = [
+            ("Artist - Album (2019) [FLAC]", album("Artist", "Album", Some(2019))),
+            ("Artist Name - Album Name [MP3 320]", album("Artist Name", "Album Name", None)),
+            ("Artist_Name-Album_Name-WEB-2019-GRP", album("Artist Name", "Album Name", Some(2019))),
+            ("Artist-Album-(CD)-FLAC-2003-GRP", album("Artist", "Album", Some(2003))),
+            ("Artist-Album-WEB-FLAC-GRP", album("Artist", "Album", None)),
+            ("Movie.Name.2019.1080p.BluRay.x264-GRP", None),
+            ("Show.Name.S01E01-GRP", None),
+            ("Artist-Album", None),
+        ];
+
+        for (name, expected) in cases {
+            assert_eq!(MusicRelease::parse(name), expected, "{}", name);
+        }
+    }
+}
@@ -0,0 +1,204 @@
+use crate::torznab::{SearchFunction, SearchCapability, SearchingCapabilities, SupportedParam};
+use crate::torznab::search_parameters::{
+    TVSearchParametersBuilder, MovieSearchParametersBuilder, MusicSearchParametersBuilder, BookSearchParametersBuilder,
+};
+
+use super::{ReleaseInfo, ContentKind, TvRelease, MovieRelease, MusicRelease, BookRelease};
+
+/// The search function and query to use for a release.
+#[derive(Debug, Clone)]
+pub struct SearchPlan {
+    pub function: SearchFunction,
+    /// `None` when the search only uses ids.
+    pub query: Option<String>,
+}
+
+impl SearchPlan {
+    /// Choose the most precise search that the indexer supports for a release,
+    /// falling back to a free text search with the name.
+    pub fn new(release: &ReleaseInfo, capabilities: &SearchingCapabilities) -> Self {
+        let plan = match release.kind {
+            ContentKind::Audio => Self::music(release, capabilities),
+            ContentKind::Book => Self::book(release, capabilities),
+            ContentKind::Video => Self::tv(release, capabilities)
+                .or_else(|| Self::movie(release, capabilities)),
+            ContentKind::Other => None,
+        };
+
+        plan.unwrap_or_else(|| SearchPlan {
+            function: SearchFunction::Search,
+            query: Some(release.name.clone()),
+        })
+    }
+
+    fn tv(release: &ReleaseInfo, capabilities: &SearchingCapabilities) -> Option<Self> {
+        let tv = TvRelease::parse(&release.name)?;
+
+        let supports = |param| capabilities.does_search_support_param(SearchCapability::TV, param);
+        if !supports(SupportedParam::Query) || !supports(SupportedParam::Season) {
+            return None;
+        }
+
+        // Season packs are searched without an episode, and indexers that
+        // can't filter by episode get the whole season.
+        let mut params = TVSearchParametersBuilder::new().season(tv.season);
+        if let (Some(episode), true) = (tv.episode, supports(SupportedParam::Episode)) {
+            params = params.episode(episode);
+        }
+
+        Some(SearchPlan {
+            function: SearchFunction::TVSearch(params.build()),
+            query: Some(tv.show),
+        })
+    }
+
+    fn movie(release: &ReleaseInfo, capabilities: &SearchingCapabilities) -> Option<Self> {
+        if !capabilities.does_support_search(SearchCapability::Movie) {
+            return None;
+        }
+
+        let supports = |param| capabilities.does_search_support_param(SearchCapability::Movie, param);
+        let mut params = MovieSearchParametersBuilder::new();
+        let mut has_id = false;
+
+        if let (Some(imdb), true) = (&release.ids.imdb, supports(SupportedParam::IMDB)) {
+            params = params.imdb_id(imdb.clone());
+            has_id = true;
+        }
+
+        if let (Some(tmdb), true) = (release.ids.tmdb, supports(SupportedParam::TMDB)) {
+            params = params.tmdb_id(tmdb);
+            has_id = true;
+        }
+
+        // An id is precise enough on its own.
+        if has_id {
+            return Some(SearchPlan {
+                function: SearchFunction::MovieSearch(params.build()),
+                query: None,
+            });
+        }
+
+        let movie = MovieRelease::parse(&release.name)?;
+        if !supports(SupportedParam::Query) {
+            return None;
+        }
+
+        let query = if supports(SupportedParam::Year) {
+            params = params.year(movie.year);
+            movie.title
+        } else {
+            format!("{} {}", movie.title, movie.year)
+        };
+
+        Some(SearchPlan {
+            function: SearchFunction::MovieSearch(params.build()),
+            query: Some(query),
+        })
+    }
+
+    fn music(release: &ReleaseInfo, capabilities: &SearchingCapabilities) -> Option<Self> {
+        // Both are requested with `t=music`.
+        let capability = [SearchCapability::Music, SearchCapability::Audio].into_iter()
+            .find(|capability| capabilities.does_support_search(capability.clone()))?;
+
+        let music = MusicRelease::parse(&release.name)?;
+        let supports = |param| capabilities.does_search_support_param(capability.clone(), param);
+
+        let mut params = MusicSearchParametersBuilder::new();
+        if let (Some(year), true) = (music.year, supports(SupportedParam::Year)) {
+            params = params.year(year);
+        }
+
+        let query = if supports(SupportedParam::Artist) && supports(SupportedParam::Album) {
+            params = params.artist(music.artist).album(music.album);
+            None
+        } else if supports(SupportedParam::Query) {
+            Some(format!("{} {}", music.artist, music.album))
+        } else {
+            return None;
+        };
+
+        Some(SearchPlan {
+            function: SearchFunction::MusicSearch(params.build()),
+            query,
+        })
+    }
+
+    fn book(release: &ReleaseInfo, capabilities: &SearchingCapabilities) -> Option<Self> {
+        if !capabilities.does_support_search(SearchCapability::Book) {
+            return None;
+        }
+
+        let book = BookRelease::parse(&release.name)?;
+        let supports = |param| capabilities.does_search_support_param(SearchCapability::Book, param);
+
+        let mut params = BookSearchParametersBuilder::new();
+        if let (Some(year), true) = (book.year, supports(SupportedParam::Year)) {
+            params = params.year(year);
+        }
+
+        let query = if supports(SupportedParam::Author) && supports(SupportedParam::Title) {
+            params = params.author(book.author).title(book.title);
+            None
+        } else if supports(SupportedParam::Query) {
+            Some(format!("{} {}", book.author, book.title))
+        } else {
+            return None;
+        };
+
+        Some(SearchPlan {
+            function: SearchFunction::BookSearch(params.build()),
+            query,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::release::MediaIds;
+
+    fn capabilities() -> SearchingCapabilities {
+        SearchingCapabilities::new(HashMap::from([
+            (SearchCapability::Search, vec![SupportedParam::Query]),
+            (SearchCapability::TV, vec![SupportedParam::Query, SupportedParam::Season, SupportedParam::Episode]),
+            (SearchCapability::Movie, vec![SupportedParam::Query, SupportedParam::IMDB]),
+        ]))
+    }
+
+    fn release(name: &str, kind: ContentKind, imdb: Option<&str>) -> ReleaseInfo {
+        ReleaseInfo {
+            name: name.to_string(),
+            kind,
+            ids: imdb.map(MediaIds::from_text).unwrap_or_default(),
+        }
+    }
+
+    #[test]
+    fn searches_video_by_tv_then_movie() {
+        let plan = SearchPlan::new(&release("Show.Name.S01E02.720p", ContentKind::Video, None), &capabilities());
+        assert!(matches!(plan.function, SearchFunction::TVSearch(_)), "{:?}", plan);
+        assert_eq!(plan.query.as_deref(), Some("Show Name"));
+
+        let plan = SearchPlan::new(&release("Movie.2019.1080p", ContentKind::Video, Some("tt0111161")), &capabilities());
+        assert!(matches!(plan.function, SearchFunction::MovieSearch(_)), "{:?}", plan);
+        assert_eq!(plan.query, None);
+    }
+
+    #[test]
+    fn searches_other_content_by_name() {
+        let cases = [
+            release("Show.Name.S01E02.720p", ContentKind::Other, None),
+            release("Movie.2019.1080p", ContentKind::Other, Some("tt0111161")),
+        ];
+
+        for release in cases {
+            let plan = SearchPlan::new(&release, &capabilities());
+            assert!(matches!(plan.function, SearchFunction::Search), "{:?}", plan);
+            assert_eq!(plan.query.as_deref(), Some(release.name.as_str()));
+        }
+    }
+}
@@ -0,0 +1,93 @@
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+use super::clean_title;
+
+/// `Show.Name.S02E05`, or `Show.Name.S02` for season packs. Only the first
+/// episode of multi-episode releases like `S02E05E06` is captured.
+static SEASON_EPISODE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)^(?P<show>.+?)[ ._-]+S(?P<season>\d{1,3})(?:[ ._-]?E(?P<episode>\d{1,4})(?:-?E\d{1,4})*)?(?:[^a-z0-9]|$)").unwrap()
+});
+
+/// `Show.Name.2x05`
+static CROSS_EPISODE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)^(?P<show>.+?)[ ._-]+(?P<season>\d{1,2})x(?P<episode>\d{2,3})(?:[^a-z0-9]|$)").unwrap()
+});
+
+/// An episode or season pack parsed from a release name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TvRelease {
+    pub show: String,
+    pub season: u16,
+    /// `None` for season packs.
+    pub episode: Option<u16>,
+}
+
+impl TvRelease {
+    /// Parse a release name like `Show.Name.S02E05.1080p.WEB.h264-GROUP`.
+    pub fn parse(name: &str) -> Option<Self> {
+        let captures = SEASON_EPISODE.captures(name)
+            .or_else(|| CROSS_EPISODE.captures(name))?;
+
+        let show = clean_title(&captures["show"]);
+        if show.is_empty() {
+            return None;
+        }
+
+        Some(TvRelease {
+            show,
+            season: captures["season"].parse().ok()?,
+            episode: match captures.name("episode") {
+                Some(episode) => Some(episode.as_str().parse().ok()?),
+                None => None,
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tv(show: &str, season: u16, episode: Option<u16>) -> Option<TvRelease> {
+        Some(TvRelease { show: show.to_string(), season, episode })
+    }
+
+    #[test]
+    fn parses_episodes_and_season_packs() {
+        let cases = [
+            ("Show.Name.S02E05.1080p.WEB.h264-GRP", tv("Show Name", 2, Some(5))),
+            ("Show Name - S01E01 - Pilot [720p]", tv("Show Name", 1, Some(1))),
+            ("show_name_s10e100_720p", tv("show name", 10, Some(100))),
+            ("The.Show.2019.S01E01.1080p.WEB", tv("The Show 2019", 1, Some(1))),
+            ("Show.Name.S01.E03.720p.HDTV", tv("Show Name", 1, Some(3))),
+            // Only the first episode of multi-episode releases.
+            ("Show.Name.S02E05E06.720p.HDTV.x264-GRP", tv("Show Name", 2, Some(5))),
+            ("Show.Name.S02E05-E06.720p.HDTV.x264-GRP", tv("Show Name", 2, Some(5))),
+            ("Show.Name.S03.1080p.BluRay.x264-GRP", tv("Show Name", 3, None)),
+            ("Show.Name.S03", tv("Show Name", 3, None)),
+            ("Show.Name.2x05.HDTV.x264-GRP", tv("Show Name", 2, Some(5))),
+            ("Show.Name.12x105", tv("Show Name", 12, Some(105))),
+        ];
+
+        for (name, expected) in cases {
+            assert_eq!(TvRelease::parse(name), expected, "{}", name);
+        }
+    }
+
+    #[test]
+    fn ignores_other_releases() {
+        let names = [
+            "Movie.Name.2019.1080p.BluRay.x264-GRP",
+            "Artist - Album (2019) [FLAC]",
+            "S01E01.mkv",
+            "Show.Name.S03Extras",
+            "Movie.Name.1920x1080.mkv",
+        ];
+
+        for name in names {
+            assert_eq!(TvRelease::parse(name), None, "{}", name);
+        }
+    }
+}
@@ -0,0 +1,172 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use tokio::sync::Semaphore;
+use tokio::task::JoinHandle;
+use tracing::{debug, info};
+
+/// Counts the finished tasks of a scheduler and logs the progress.
+#[derive(Debug, Default)]
+pub struct Progress {
+    total: AtomicUsize,
+    completed: AtomicUsize,
+}
+
+impl Progress {
+    pub fn total(&self) -> usize {
+        self.total.load(Ordering::SeqCst)
+    }
+
+    pub fn completed(&self) -> usize {
+        self.completed.load(Ordering::SeqCst)
+    }
+
+    fn complete(&self) {
+        let completed = self.completed.fetch_add(1, Ordering::SeqCst) + 1;
+        let total = self.total();
+
+        // Log at every percent so large libraries don't flood the log.
+        let step = (total / 100).max(1);
+        if completed.is_multiple_of(step) || completed == total {
+            info!("Progress: {}/{} searches ({:.0}%)", completed, total, completed as f64 / total as f64 * 100.0);
+        } else {
+            debug!("Progress: {}/{} searches", completed, total);
+        }
+    }
+}
+
+/// Runs tasks while limiting how many run at the same time, both in total and
+/// for each indexer.
+#[derive(Debug)]
+pub struct Scheduler {
+    global: Arc<Semaphore>,
+    indexers: HashMap<String, Arc<Semaphore>>,
+    progress: Arc<Progress>,
+}
+
+impl Scheduler {
+    pub fn new(max_concurrency: usize) -> Self {
+        Scheduler {
+            global: Arc::new(Semaphore::new(max_concurrency)),
+            indexers: HashMap::new(),
+            progress: Arc::new(Progress::default()),
+        }
+    }
+
+    /// Limit the amount of tasks that can run at the same time for an indexer.
+    pub fn add_indexer(&mut self, name: &str, max_concurrency: usize) {
+        self.indexers.insert(name.to_string(), Arc::new(Semaphore::new(max_concurrency)));
+    }
+
+    pub fn progress(&self) -> &Progress {
+        &self.progress
+    }
+
+    /// Spawn the tasks, each with the name of its indexer. A task starts once
+    /// both its indexer and the global limit allow it.
+    pub fn spawn_all<F, T>(&self, tasks: Vec<(String, F)>) -> Vec<JoinHandle<T>>
+    where
+        F: Future<Output = T> + Send + 'static,
+        T: Send + 'static,
+    {
+        // Counted before any task can finish, so the first progress is right.
+        self.progress.total.fetch_add(tasks.len(), Ordering::SeqCst);
+
+        tasks.into_iter()
+            .map(|(indexer, task)| self.spawn(&indexer, task))
+            .collect()
+    }
+
+    fn spawn<F, T>(&self, indexer: &str, task: F) -> JoinHandle<T>
+    where
+        F: Future<Output = T> + Send + 'static,
+        T: Send + 'static,
+    {
+        let global = Arc::clone(&self.global);
+        let indexer = self.indexers.get(indexer).map(Arc::clone);
+        let progress = Arc::clone(&self.progress);
+
+        tokio::spawn(async move {
+            // The indexer permit is taken first so tasks waiting on a busy
+            // indexer don't hold global permits that other indexers could use.
+            let _indexer_permit = match &indexer {
+                Some(semaphore) => Some(semaphore.acquire().await.expect("Scheduler semaphore was closed")),
+                None => None,
+            };
+            let _global_permit = global.acquire().await.expect("Scheduler semaphore was closed");
+
+            let output = task.await;
+            progress.complete();
+
+            output
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    /// Counts the tasks that are running and remembers the most at once.
+    #[derive(Debug, Default)]
+    struct Peak {
+        running: AtomicUsize,
+        peak: AtomicUsize,
+    }
+
+    impl Peak {
+        async fn run(&self) {
+            let running = self.running.fetch_add(1, Ordering::SeqCst) + 1;
+            self.peak.fetch_max(running, Ordering::SeqCst);
+
+            tokio::time::sleep(Duration::from_millis(10)).await;
+
+            self.running.fetch_sub(1, Ordering::SeqCst);
+        }
+
+        fn peak(&self) -> usize {
+            self.peak.load(Ordering::SeqCst)
+        }
+    }
+
+    #[tokio::test]
+    async fn limits_the_tasks_running_at_once() {
+        let mut scheduler = Scheduler::new(3);
+        scheduler.add_indexer("slow", 1);
+        scheduler.add_indexer("fast", 2);
+
+        let global = Arc::new(Peak::default());
+        let indexers: HashMap<&str, Arc<Peak>> = ["slow", "fast", "unlimited"].into_iter()
+            .map(|name| (name, Arc::new(Peak::default())))
+            .collect();
+
+        let tasks = (0..30)
+            .map(|i| {
+                let name = ["slow", "fast", "unlimited"][i % 3];
+                let global = Arc::clone(&global);
+                let indexer = Arc::clone(&indexers[name]);
+
+                (name.to_string(), async move {
+                    tokio::join!(global.run(), indexer.run());
+                })
+            })
+            .collect();
+
+        let handles = scheduler.spawn_all(tasks);
+        assert_eq!(scheduler.progress().total(), 30);
+
+        for handle in handles {
+            handle.await.unwrap();
+        }
+
+        assert_eq!(global.peak(), 3);
+        assert_eq!(indexers["slow"].peak(), 1);
+        assert_eq!(indexers["fast"].peak(), 2);
+        assert!(indexers["unlimited"].peak() <= 3);
+        assert_eq!(scheduler.progress().completed(), 30);
+    }
+}
@@ -0,0 +1,48 @@
+//! The torrents of a live torrent client.
+
+use async_trait::async_trait;
+use tracing::debug;
+
+use crate::clients::TorrentClient;
+use crate::release::MediaIds;
+
+use super::{SourceError, SourceItem, TorrentSource};
+
+pub struct ClientSource {
+    client: Box<dyn TorrentClient>,
+}
+
+impl ClientSource {
+    pub fn new(client: Box<dyn TorrentClient>) -> Self {
+        ClientSource { client }
+    }
+}
+
+#[async_trait]
+impl TorrentSource for ClientSource {
+    fn describe(&self) -> String {
+        format!("the torrent client {}", self.client.name())
+    }
+
+    /// The torrents that are fully downloaded and seedable.
+    async fn items(&self) -> Result<Vec<SourceItem>, SourceError> {
+        let torrents = self.client.torrents().await?;
+        let total = torrents.len();
+
+        let items: Vec<SourceItem> = torrents.into_iter()
+            .filter(|torrent| torrent.is_complete())
+            .map(|torrent| SourceItem {
+                name: torrent.name,
+                info_hash: Some(torrent.info_hash),
+                files: torrent.files,
+                ids: MediaIds::default(),
+                save_path: Some(torrent.save_path),
+                category: torrent.category,
+                tags: torrent.tags,
+            })
+            .collect();
+        debug!("Skipped {} incomplete torrents of {}", total - items.len(), self.client.name());
+
+        Ok(items)
+    }
+}
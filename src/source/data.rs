@@ -0,0 +1,110 @@
+//! Files on disk without a .torrent, like old downloads whose torrents are gone.
+
+use std::path::{Path, PathBuf};
+
+use async_trait::async_trait;
+use tracing::warn;
+
+use crate::matching::{FileEntry, FileList};
+use crate::release::MediaIds;
+
+use super::{SourceError, SourceItem, TorrentSource};
+
+/// The files in `path` and its subdirectories, with `root` as the first component of their paths.
+///
+/// Symbolic links are followed, unless they lead back to a directory that
+/// contains them, which would never end.
+fn read_files(path: &Path, root: &Path) -> std::io::Result<Vec<FileEntry>> {
+    read_tree(path, root, &mut Vec::new())
+}
+
+/// `ancestors` are the canonical paths of the directories that are being read.
+fn read_tree(path: &Path, root: &Path, ancestors: &mut Vec<PathBuf>) -> std::io::Result<Vec<FileEntry>> {
+    let metadata = path.metadata()?;
+    if metadata.is_file() {
+        return Ok(vec![FileEntry { path: root.to_path_buf(), length: metadata.len() }]);
+    }
+
+    let canonical = path.canonicalize()?;
+    if ancestors.contains(&canonical) {
+        warn!("Skipped {}, it links to a directory that contains it", path.display());
+        return Ok(Vec::new());
+    }
+    ancestors.push(canonical);
+
+    let mut files = Vec::new();
+    for entry in path.read_dir()? {
+        let entry = entry?;
+        let mut inner = read_tree(&entry.path(), &root.join(entry.file_name()), ancestors)?;
+        files.append(&mut inner);
+    }
+
+    ancestors.pop();
+
+    Ok(files)
+}
+
+/// Directories where each file or folder at the top is searched, like a torrent with that name.
+#[derive(Debug, Clone)]
+pub struct DataDirs {
+    paths: Vec<PathBuf>,
+}
+
+impl DataDirs {
+    pub fn new(paths: Vec<PathBuf>) -> Self {
+        DataDirs { paths }
+    }
+
+    fn read_dir(path: &Path) -> std::io::Result<Vec<SourceItem>> {
+        let mut items = Vec::new();
+        for entry in path.read_dir()? {
+            let entry = entry?;
+            let name = entry.file_name().to_string_lossy().into_owned();
+
+            // Hidden files are usually metadata of other programs.
+            if name.starts_with('.') {
+                continue;
+            }
+
+            let files = match read_files(&entry.path(), Path::new(&name)) {
+                Ok(files) if !files.is_empty() => files,
+                Ok(_) => continue,
+                Err(e) => {
+                    warn!("Failed to read {}: {}", entry.path().display(), e);
+                    continue;
+                }
+            };
+
+            items.push(SourceItem {
+                files: FileList::new(name.clone(), files),
+                name,
+                info_hash: None,
+                ids: MediaIds::default(),
+                save_path: Some(path.display().to_string()),
+                category: None,
+                tags: Vec::new(),
+            });
+        }
+
+        items.sort_by(|a, b| a.name.cmp(&b.name));
+
+        Ok(items)
+    }
+}
+
+#[async_trait]
+impl TorrentSource for DataDirs {
+    fn describe(&self) -> String {
+        let paths: Vec<String> = self.paths.iter().map(|path| path.display().to_string()).collect();
+        paths.join(", ")
+    }
+
+    async fn items(&self) -> Result<Vec<SourceItem>, SourceError> {
+        let mut items = Vec::new();
+        for path in self.paths.iter() {
+            items.append(&mut Self::read_dir(path)?);
+        }
+
+        Ok(items)
+    }
+}
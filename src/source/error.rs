@@ -0,0 +1,34 @@
+use std::fmt;
+
+use crate::clients::TorrentClientError;
+
+#[derive(Debug)]
+pub enum SourceError {
+    /// The directory of .torrent files or data couldn't be read.
+    IoError(std::io::Error),
+    /// The torrents couldn't be listed by the client.
+    ClientError(TorrentClientError),
+}
+
+impl fmt::Display for SourceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SourceError::IoError(e) => write!(f, "io error: {}", e),
+            SourceError::ClientError(e) => write!(f, "torrent client error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for SourceError {}
+
+impl From<std::io::Error> for SourceError {
+    fn from(e: std::io::Error) -> Self {
+        SourceError::IoError(e)
+    }
+}
+
+impl From<TorrentClientError> for SourceError {
+    fn from(e: TorrentClientError) -> Self {
+        SourceError::ClientError(e)
+    }
+}
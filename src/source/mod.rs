@@ -0,0 +1,73 @@
+//! Where the torrents to cross-seed come from.
+
+pub mod error;
+pub use error::*;
+
+pub mod torrent_dir;
+pub use torrent_dir::*;
+
+pub mod client;
+pub use client::*;
+
+pub mod data;
+pub use data::*;
+
+use std::path::Path;
+
+use async_trait::async_trait;
+use lava_torrent::torrent::v1::Torrent;
+
+use crate::matching::FileList;
+use crate::release::{IdMap, MediaIds, ReleaseInfo};
+
+/// A torrent, or files on disk, to search cross-seeds for.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SourceItem {
+    pub name: String,
+    /// The lowercase v1 info hash, `None` for data without a torrent.
+    pub info_hash: Option<String>,
+    pub files: FileList,
+    /// Ids found in the torrent itself, like in its comment.
+    pub ids: MediaIds,
+    /// Where the data is, if the source knows it.
+    pub save_path: Option<String>,
+    pub category: Option<String>,
+    pub tags: Vec<String>,
+}
+
+impl SourceItem {
+    pub fn from_torrent(torrent: &Torrent) -> Self {
+        SourceItem {
+            name: torrent.name.clone(),
+            info_hash: Some(torrent.info_hash()),
+            files: FileList::from(torrent),
+            ids: MediaIds::from_torrent(torrent),
+            save_path: None,
+            category: None,
+            tags: Vec::new(),
+        }
+    }
+
+    /// What is searched for, using the id map for ids the item doesn't have.
+    pub fn release(&self, id_map: Option<&IdMap>) -> ReleaseInfo {
+        ReleaseInfo::from_files(&self.files, self.info_hash.as_deref(), self.ids.clone(), id_map)
+    }
+
+    /// Identifies the item in the state database: the info hash, or the path of data.
+    pub fn state_key(&self) -> String {
+        match (&self.info_hash, &self.save_path) {
+            (Some(info_hash), _) => info_hash.clone(),
+            (None, Some(save_path)) => Path::new(save_path).join(&self.name).display().to_string(),
+            (None, None) => self.name.clone(),
+        }
+    }
+}
+
+/// A source of torrents, like a directory of .torrent files, a torrent client or data directories.
+#[async_trait]
+pub trait TorrentSource: Send + Sync {
+    /// Where the torrents come from, for the logs.
+    fn describe(&self) -> String;
+
+    async fn items(&self) -> Result<Vec<SourceItem>, SourceError>;
+}
@@ -0,0 +1,59 @@
+//! .torrent files in a directory, like the `BT_backup` folder of qBittorrent.
+
+use std::path::{Path, PathBuf};
+
+use async_trait::async_trait;
+use lava_torrent::torrent::v1::Torrent;
+use tracing::warn;
+
+use super::{SourceError, SourceItem, TorrentSource};
+
+/// Find the .torrent files in a directory and its subdirectories.
+pub fn read_torrents(path: &Path) -> std::io::Result<Vec<PathBuf>> {
+    let mut torrents = Vec::new();
+    for entry in path.read_dir()? {
+        let entry = entry?;
+        let path = entry.path();
+        if path.is_file() {
+            if path.extension().is_some_and(|extension| extension == "torrent") {
+                torrents.push(path);
+            }
+        } else {
+            let mut inner = read_torrents(&path)?;
+            torrents.append(&mut inner);
+        }
+    }
+
+    Ok(torrents)
+}
+
+#[derive(Debug, Clone)]
+pub struct TorrentDir {
+    path: PathBuf,
+}
+
+impl TorrentDir {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        TorrentDir { path: path.into() }
+    }
+}
+
+#[async_trait]
+impl TorrentSource for TorrentDir {
+    fn describe(&self) -> String {
+        self.path.display().to_string()
+    }
+
+    /// Read every .torrent file, skipping the ones that can't be parsed.
+    async fn items(&self) -> Result<Vec<SourceItem>, SourceError> {
+        let mut items = Vec::new();
+        for path in read_torrents(&self.path)? {
+            match Torrent::read_from_file(&path) {
+                Ok(torrent) => items.push(SourceItem::from_torrent(&torrent)),
+                Err(e) => warn!("Failed to read {}: {}", path.display(), e),
+            }
+        }
+
+        Ok(items)
+    }
+}
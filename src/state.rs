@@ -0,0 +1,217 @@
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use rusqlite::{params, Connection, OptionalExtension};
+
+use crate::matching::{MatchDecision, MatchResult};
+use crate::torznab::TorrentResult;
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS searches (
+        info_hash TEXT NOT NULL,
+        indexer TEXT NOT NULL,
+        searched_at INTEGER NOT NULL,
+        PRIMARY KEY (info_hash, indexer)
+    );
+
+    CREATE TABLE IF NOT EXISTS decisions (
+        info_hash TEXT NOT NULL,
+        indexer TEXT NOT NULL,
+        candidate TEXT NOT NULL,
+        name TEXT NOT NULL,
+        decision TEXT NOT NULL,
+        reason TEXT NOT NULL,
+        decided_at INTEGER NOT NULL,
+        PRIMARY KEY (info_hash, indexer, candidate)
+    );
+";
+
+fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or_default()
+}
+
+/// The key a candidate is stored under. The guid is preferred since the
+/// download link can change between searches.
+fn candidate_key(result: &TorrentResult) -> &str {
+    result.guid.as_deref().unwrap_or(&result.link)
+}
+
+/// Remembers the searches and match decisions of previous runs.
+#[derive(Debug)]
+pub struct StateDb {
+    conn: Mutex<Connection>,
+}
+
+impl StateDb {
+    /// Open the database at the path, creating it if it doesn't exist.
+    pub fn open(path: &Path) -> rusqlite::Result<Self> {
+        Self::from_connection(Connection::open(path)?)
+    }
+
+    pub fn open_in_memory() -> rusqlite::Result<Self> {
+        Self::from_connection(Connection::open_in_memory()?)
+    }
+
+    fn from_connection(conn: Connection) -> rusqlite::Result<Self> {
+        conn.execute_batch(SCHEMA)?;
+
+        Ok(StateDb {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    /// Returns true if the torrent was searched on the indexer less than `max_age` ago.
+    pub fn is_search_fresh(&self, info_hash: &str, indexer: &str, max_age: Duration) -> rusqlite::Result<bool> {
+        let conn = self.conn.lock().unwrap();
+        let searched_at: Option<i64> = conn.query_row(
+            "SELECT searched_at FROM searches WHERE info_hash = ?1 AND indexer = ?2",
+            params![info_hash, indexer],
+            |row| row.get(0),
+        ).optional()?;
+
+        Ok(match searched_at {
+            Some(searched_at) => now() - searched_at < i64::try_from(max_age.as_secs()).unwrap_or(i64::MAX),
+            None => false,
+        })
+    }
+
+    pub fn record_search(&self, info_hash: &str, indexer: &str) -> rusqlite::Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT OR REPLACE INTO searches (info_hash, indexer, searched_at) VALUES (?1, ?2, ?3)",
+            params![info_hash, indexer, now()],
+        )?;
+
+        Ok(())
+    }
+
+    /// The decision made for a search result in an earlier search.
+    pub fn decision(&self, info_hash: &str, indexer: &str, result: &TorrentResult) -> rusqlite::Result<Option<MatchDecision>> {
+        let conn = self.conn.lock().unwrap();
+        let decision: Option<String> = conn.query_row(
+            "SELECT decision FROM decisions WHERE info_hash = ?1 AND indexer = ?2 AND candidate = ?3",
+            params![info_hash, indexer, candidate_key(result)],
+            |row| row.get(0),
+        ).optional()?;
+
+        Ok(decision.and_then(|d| d.parse().ok()))
+    }
+
+    pub fn record_decision(&self, info_hash: &str, indexer: &str, result: &TorrentResult, decision: &MatchResult) -> rusqlite::Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT OR REPLACE INTO decisions (info_hash, indexer, candidate, name, decision, reason, decided_at)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                info_hash,
+                indexer,
+                candidate_key(result),
+                result.name,
+                decision.decision.as_str(),
+                decision.reason.to_string(),
+                now(),
+            ],
+        )?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::matching::MatchReason;
+    use crate::torznab::TorznabAttributes;
+
+    const HOUR: Duration = Duration::from_secs(60 * 60);
+
+    fn result(link: &str, guid: Option<&str>) -> TorrentResult {
+        TorrentResult {
+            name: "Release".to_string(),
+            link: link.to_string(),
+            size: None,
+            categories: Vec::new(),
+            pub_date: None,
+            guid: guid.map(str::to_string),
+            comments: None,
+            enclosure: None,
+            attributes: TorznabAttributes::default(),
+        }
+    }
+
+    /// Pretend the torrent was searched on the indexer `ago`.
+    fn searched(db: &StateDb, info_hash: &str, indexer: &str, ago: Duration) {
+        let conn = db.conn.lock().unwrap();
+        conn.execute(
+            "INSERT OR REPLACE INTO searches (info_hash, indexer, searched_at) VALUES (?1, ?2, ?3)",
+            params![info_hash, indexer, now() - ago.as_secs() as i64],
+        ).unwrap();
+    }
+
+    #[test]
+    fn searches_are_fresh_within_the_interval() {
+        let db = StateDb::open_in_memory().unwrap();
+        assert!(!db.is_search_fresh("abc", "tracker", HOUR).unwrap());
+
+        db.record_search("abc", "tracker").unwrap();
+        assert!(db.is_search_fresh("abc", "tracker", HOUR).unwrap());
+        assert!(!db.is_search_fresh("abc", "tracker", Duration::ZERO).unwrap());
+        assert!(!db.is_search_fresh("abc", "other", HOUR).unwrap());
+        assert!(!db.is_search_fresh("def", "tracker", HOUR).unwrap());
+        assert!(db.is_search_fresh("abc", "tracker", Duration::from_secs(u64::MAX)).unwrap());
+
+        let cases = [
+            (Duration::from_secs(59 * 60), true),
+            (HOUR, false),
+            (2 * HOUR, false),
+        ];
+
+        for (ago, expected) in cases {
+            searched(&db, "abc", "tracker", ago);
+            assert_eq!(db.is_search_fresh("abc", "tracker", HOUR).unwrap(), expected, "{:?}", ago);
+        }
+    }
+
+    #[test]
+    fn searches_are_recorded_once_per_torrent_and_indexer() {
+        let db = StateDb::open_in_memory().unwrap();
+        searched(&db, "abc", "tracker", 2 * HOUR);
+
+        db.record_search("abc", "tracker").unwrap();
+        db.record_search("abc", "other").unwrap();
+        assert!(db.is_search_fresh("abc", "tracker", HOUR).unwrap());
+
+        let rows: i64 = db.conn.lock().unwrap()
+            .query_row("SELECT COUNT(*) FROM searches", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(rows, 2);
+    }
+
+    #[test]
+    fn decisions_are_keyed_by_guid_then_link() {
+        let db = StateDb::open_in_memory().unwrap();
+        let mismatch = MatchResult::mismatch(MatchReason::NoFilesMatch);
+
+        let with_guid = result("http://indexer/dl/1?token=a", Some("guid-1"));
+        db.record_decision("abc", "tracker", &with_guid, &mismatch).unwrap();
+
+        // The download link changed, but the guid is the same.
+        let relinked = result("http://indexer/dl/1?token=b", Some("guid-1"));
+        assert_eq!(db.decision("abc", "tracker", &relinked).unwrap(), Some(MatchDecision::Mismatch));
+        assert_eq!(db.decision("abc", "other", &relinked).unwrap(), None);
+        assert_eq!(db.decision("def", "tracker", &relinked).unwrap(), None);
+
+        let without_guid = result("http://indexer/dl/2", None);
+        assert_eq!(db.decision("abc", "tracker", &without_guid).unwrap(), None);
+        db.record_decision("abc", "tracker", &without_guid, &mismatch).unwrap();
+        assert_eq!(db.decision("abc", "tracker", &without_guid).unwrap(), Some(MatchDecision::Mismatch));
+
+        let matched = MatchResult::new(MatchDecision::Match, MatchReason::IdenticalFiles { files: 1 });
+        db.record_decision("abc", "tracker", &relinked, &matched).unwrap();
+        assert_eq!(db.decision("abc", "tracker", &with_guid).unwrap(), Some(MatchDecision::Match));
+    }
+}
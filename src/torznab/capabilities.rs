@@ -10,6 +10,22 @@ pub enum SupportedParam {
     IMDB,
     TMDB,
     TVDB,
+    TVMaze,
+    /// Id of the show on TVRage.
+    Rid,
+    Trakt,
+    Douban,
+    Genre,
+    Year,
+    Artist,
+    Album,
+    Label,
+    Track,
+    Author,
+    Title,
+    Publisher,
+    /// A parameter that isn't in the Torznab or Newznab specification.
+    Unknown(String),
 }
 
 impl From<String> for SupportedParam {
@@ -21,30 +37,35 @@ impl From<String> for SupportedParam {
             "imdbid" => SupportedParam::IMDB,
             "tmdbid" => SupportedParam::TMDB,
             "tvdbid" => SupportedParam::TVDB,
-            _ => panic!("Unsupported param: {}", s),
+            "tvmazeid" => SupportedParam::TVMaze,
+            "rid" => SupportedParam::Rid,
+            "traktid" => SupportedParam::Trakt,
+            "doubanid" => SupportedParam::Douban,
+            "genre" => SupportedParam::Genre,
+            "year" => SupportedParam::Year,
+            "artist" => SupportedParam::Artist,
+            "album" => SupportedParam::Album,
+            "label" => SupportedParam::Label,
+            "track" => SupportedParam::Track,
+            "author" => SupportedParam::Author,
+            "title" => SupportedParam::Title,
+            "publisher" => SupportedParam::Publisher,
+            _ => SupportedParam::Unknown(s),
         }
     }
 }
 
 #[derive(Debug, Hash, PartialEq, Eq, Clone, Deserialize)]
+#[serde(from = "String")]
 pub enum SearchCapability {
-    #[serde(rename = "search")]
     Search,
-
-    #[serde(rename = "tv-search")]
     TV,
-    
-    #[serde(rename = "movie-search")]
     Movie,
-    
-    #[serde(rename = "music-search")]
     Music,
-    
-    #[serde(rename = "audio-search")]
     Audio,
-    
-    #[serde(rename = "book-search")]
-    Book
+    Book,
+    /// A search function that isn't in the Torznab or Newznab specification.
+    Unknown(String),
 }
 
 impl From<String> for SearchCapability {
@@ -55,13 +76,13 @@ impl From<String> for SearchCapability {
             "movie-search" => SearchCapability::Movie,
             "music-search" => SearchCapability::Music,
             "audio-search" => SearchCapability::Audio,
-            "book" => SearchCapability::Book,
-            _ => panic!("Unsupported param: {}", s),
+            "book-search" => SearchCapability::Book,
+            _ => SearchCapability::Unknown(s),
         }
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
 pub struct SearchingCapabilities {
     supported_functions: HashMap<SearchCapability, Vec<SupportedParam>>,
 }
@@ -87,20 +108,12 @@ impl SearchingCapabilities {
     }
 }
 
-impl Default for SearchingCapabilities {
-    fn default() -> Self {
-        SearchingCapabilities {
-            supported_functions: HashMap::new(),
-        }
-    }
-}
-
 impl<'de> Deserialize<'de> for SearchingCapabilities {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
         D: serde::Deserializer<'de>,
     {
-        let raw: HashMap<SearchCapability, HashMap<String, String>> = Deserialize::deserialize(deserializer).unwrap();
+        let raw: HashMap<SearchCapability, HashMap<String, String>> = Deserialize::deserialize(deserializer)?;
         let mut functions: HashMap<SearchCapability, Vec<SupportedParam>> = HashMap::new();
 
         for (key, value) in raw.iter() {
@@ -111,7 +124,11 @@ impl<'de> Deserialize<'de> for SearchingCapabilities {
 
             if available {
                 if let Some(params) = value.get("supportedParams") {
-                    for param in params.split(',') {
+                    let params = params.split(',')
+                        .map(str::trim)
+                        .filter(|param| !param.is_empty());
+
+                    for param in params {
                         supported_params.push(param.to_string().into());
                     }
                 }
@@ -136,9 +153,24 @@ pub struct Category {
     sub_categories: Option<Vec<Category>>,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+impl Category {
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn sub_categories(&self) -> &[Category] {
+        self.sub_categories.as_deref().unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default, Deserialize)]
 pub struct Categories {
-    #[serde(rename = "category")]
+    /// Empty for `<categories/>`, which some trackers send.
+    #[serde(rename = "category", default)]
     pub categories: Vec<Category>,
 }
 
@@ -148,27 +180,96 @@ impl Categories {
     }
 }
 
-impl Default for Categories {
-    fn default() -> Self {
-        Categories {
-            categories: Vec::new(),
+/// The limits on the amount of results of a search.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub struct Limits {
+    /// The largest `limit` the indexer allows.
+    pub max: Option<u32>,
+    /// The amount of results when no `limit` is given.
+    pub default: Option<u32>,
+}
+
+/// Information about the indexer.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Server {
+    pub title: Option<String>,
+    pub version: Option<String>,
+    pub strapline: Option<String>,
+    pub email: Option<String>,
+    pub url: Option<String>,
+    pub image: Option<String>,
+}
+
+impl Server {
+    /// The title and version, for logging.
+    pub fn identity(&self) -> Option<String> {
+        match (&self.title, &self.version) {
+            (Some(title), Some(version)) => Some(format!("{} {}", title, version)),
+            (Some(title), None) => Some(title.clone()),
+            (None, Some(version)) => Some(format!("version {}", version)),
+            (None, None) => None,
         }
     }
 }
 
+/// Whether new users can sign up. Missing attributes mean no.
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Registration {
+    #[serde(default, deserialize_with = "deserialize_yes_no")]
+    pub available: bool,
+    #[serde(default, deserialize_with = "deserialize_yes_no")]
+    pub open: bool,
+}
+
+/// A tag that search results can have, like `freeleech` or `internal`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Tag {
+    pub name: String,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default, Deserialize)]
+pub struct Tags {
+    #[serde(rename = "tag", default)]
+    pub tags: Vec<Tag>,
+}
+
+impl Tags {
+    pub fn get(&self, name: &str) -> Option<&Tag> {
+        self.tags.iter().find(|tag| tag.name.eq_ignore_ascii_case(name))
+    }
+}
+
+fn deserialize_yes_no<'de, D>(deserializer: D) -> Result<bool, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let value: String = Deserialize::deserialize(deserializer)?;
+    Ok(value.eq_ignore_ascii_case("yes") || value.eq_ignore_ascii_case("true"))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default, Deserialize)]
 pub struct Capabilities {
+    pub server: Option<Server>,
+    pub limits: Option<Limits>,
+    pub registration: Option<Registration>,
+
+    #[serde(default)]
     pub categories: Categories,
     
     #[serde(rename = "searching")]
     pub searching_capabilities: SearchingCapabilities,
+
+    #[serde(default)]
+    pub tags: Tags,
 }
 
-impl Default for Capabilities {
-    fn default() -> Self {
-        Capabilities {
-            categories: Categories::default(),
-            searching_capabilities: SearchingCapabilities::default(),
+impl Capabilities {
+    /// Clamp a search limit to the maximum the indexer allows.
+    pub fn clamp_limit(&self, limit: i32) -> i32 {
+        match self.limits.and_then(|l| l.max) {
+            Some(max) => limit.min(i32::try_from(max).unwrap_or(i32::MAX)),
+            None => limit,
         }
     }
 }
\ No newline at end of file
@@ -1,12 +1,35 @@
-use super::{Capabilities, TorznabFunction, SearchFunction, GenericSearchParameters, TorrentResult, ClientError};
+use std::collections::HashSet;
+use std::fmt;
+use std::sync::Arc;
+
+use super::{Capabilities, TorznabFunction, SearchFunction, GenericSearchParameters, SearchResponse, TorrentResult, ClientError, RateLimit, RateLimiter};
+use super::{RetryPolicy, IndexerHealth, ApiError, is_fatal_status, is_retryable_status, is_retryable_error, parse_retry_after};
+use super::search_parameters::QueryPair;
 
 use bytes::Bytes;
 use bytes::Buf;
 
+use futures::{stream, Stream, TryStreamExt};
 use rss::Channel;
-use tracing::{span, event, debug, info, Level};
+use tracing::{span, debug, warn, Level};
+use url::Url;
+
+use crate::redact::redact;
+
+/// The page size used when neither the search nor the capabilities set one.
+const DEFAULT_PAGE_SIZE: i32 = 100;
+
+/// The state of a paginated search between pages.
+struct PageState {
+    offset: i32,
+    /// The amount of results returned so far.
+    returned: usize,
+    /// Keys of the results that were already returned.
+    seen: HashSet<String>,
+    done: bool,
+}
 
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct TorznabClient {
     http: reqwest::Client,
     pub name: String,
@@ -14,6 +37,22 @@ pub struct TorznabClient {
     api_key: String,
     pub capabilities: Capabilities,
     pub client_span: tracing::Span,
+    /// Shared between all clones of the client.
+    rate_limiter: Option<Arc<RateLimiter>>,
+    retry_policy: RetryPolicy,
+    /// Shared between all clones of the client.
+    health: Arc<IndexerHealth>,
+}
+
+impl fmt::Debug for TorznabClient {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TorznabClient")
+            .field("name", &self.name)
+            .field("base_url", &redact(&self.base_url))
+            .field("capabilities", &self.capabilities)
+            .field("retry_policy", &self.retry_policy)
+            .finish_non_exhaustive()
+    }
 }
 
 impl TorznabClient {
@@ -30,19 +69,15 @@ impl TorznabClient {
             api_key: api_key.to_string(),
             capabilities: Capabilities::default(),
             client_span: Self::client_span(&name),
+            rate_limiter: None,
+            retry_policy: RetryPolicy::default(),
+            health: Arc::new(IndexerHealth::default()),
         }
     }
 
     /// Construct a new client and get the capabilities.
-    pub async fn new(name: String, base_url: &str, api_key: &str) -> Result<Self, reqwest::Error> {
-        let mut client = TorznabClient {
-            name: name.clone(),
-            http: reqwest::Client::new(),
-            base_url: base_url.to_string(),
-            api_key: api_key.to_string(),
-            capabilities: Capabilities::default(),
-            client_span: Self::client_span(&name),
-        };
+    pub async fn new(name: String, base_url: &str, api_key: &str) -> Result<Self, ClientError> {
+        let mut client = Self::new_no_capabilities(name, base_url, api_key);
 
         // Get capabilities and store them in the client before returning
         client.store_capabilities().await?;
@@ -50,54 +85,265 @@ impl TorznabClient {
         Ok(client)
     }
 
+    /// Limit the requests sent by this client and all of its clones.
+    pub fn with_rate_limit(mut self, rate_limit: &RateLimit) -> Self {
+        self.rate_limiter = Some(Arc::new(RateLimiter::new(rate_limit)));
+        self
+    }
+
+    /// Set how failed requests are retried.
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// The reason the indexer was disabled for the rest of the run, if it was.
+    pub fn disabled_reason(&self) -> Option<String> {
+        self.health.disabled_reason()
+    }
+
+    /// Wait until the rate limit allows another request.
+    async fn wait_for_rate_limit(&self) {
+        if let Some(limiter) = &self.rate_limiter {
+            limiter.acquire().await;
+        }
+    }
+
+    /// Send a GET request, retrying when the indexer is rate limiting or unavailable.
+    ///
+    /// Server errors and connection problems count towards disabling the
+    /// indexer, other errors like a missing download only fail the request.
+    async fn get(&self, url: &str) -> Result<Bytes, ClientError> {
+        if let Some(reason) = self.health.disabled_reason() {
+            return Err(ClientError::IndexerDisabled(reason));
+        }
+
+        let mut attempt = 0;
+        loop {
+            self.wait_for_rate_limit().await;
+
+            // A body that breaks off is a failed request like one that couldn't be sent.
+            let sent = async {
+                let res = self.http.get(url).send().await?;
+                match res.error_for_status_ref() {
+                    Ok(_) => Ok(Ok(res.bytes().await?)),
+                    Err(e) => Ok(Err((res, e))),
+                }
+            };
+
+            // Find out if the request should be retried, and how long the indexer asked us to wait.
+            // The errors are converted right away, which redacts the api key from their url.
+            let (error, status, retry_after) = match sent.await {
+                Ok(Ok(bytes)) => {
+                    self.health.record_success();
+                    return Ok(bytes);
+                },
+                Ok(Err((res, e))) if is_retryable_status(res.status()) => {
+                    (ClientError::from(e), Some(res.status()), parse_retry_after(res.headers()))
+                },
+                Ok(Err((res, e))) => {
+                    let error = ClientError::from(e);
+                    let status = res.status();
+
+                    // Indexers can explain the error in the body.
+                    let body = res.bytes().await.unwrap_or_default();
+                    self.check_api_error(&body)?;
+
+                    // Rejected credentials won't work for any other request either.
+                    if is_fatal_status(status) {
+                        self.health.disable(error.to_string());
+                    } else if status.is_server_error() {
+                        self.health.record_failure(error.to_string(), self.retry_policy.disable_after);
+                    }
+
+                    return Err(error);
+                },
+                Err(e) if is_retryable_error(&e) => (ClientError::from(e), None, None),
+                Err(e) => return Err(ClientError::from(e)),
+            };
+
+            if attempt >= self.retry_policy.max_retries {
+                // An indexer that is only rate limiting works again after waiting.
+                if status.is_none_or(|status| status.is_server_error()) {
+                    self.health.record_failure(error.to_string(), self.retry_policy.disable_after);
+                }
+
+                return Err(error);
+            }
+
+            let delay = self.retry_policy.delay(attempt, retry_after);
+            warn!("Request failed ({}), retrying in {:.1}s", error, delay.as_secs_f64());
+
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        }
+    }
+
+    /// Returns the error if the body is a Torznab error response.
+    ///
+    /// Errors that stop the indexer from answering any more requests disable it.
+    fn check_api_error(&self, body: &[u8]) -> Result<(), ClientError> {
+        let error = std::str::from_utf8(body).ok().and_then(ApiError::from_body);
+
+        match error {
+            Some(error) => {
+                if error.is_fatal() {
+                    self.health.disable(error.to_string());
+                }
+
+                Err(error.into())
+            },
+            None => Ok(()),
+        }
+    }
+
+    /// Build the url of a request to the indexer.
+    ///
+    /// The query of the base url is kept, like the `?` of Jackett urls or
+    /// extra parameters, unless the request sets the same parameter.
+    fn url(&self, pairs: &[QueryPair]) -> Result<Url, ClientError> {
+        let mut url = Url::parse(&self.base_url)?;
+
+        let existing: Vec<(String, String)> = url.query_pairs()
+            .into_owned()
+            .filter(|(key, _)| key != "apikey" && !pairs.iter().any(|(k, _)| k == key))
+            .collect();
+
+        url.query_pairs_mut()
+            .clear()
+            .extend_pairs(existing)
+            .append_pair("apikey", &self.api_key)
+            .extend_pairs(pairs.iter().map(|(key, value)| (*key, value.as_str())));
+
+        Ok(url)
+    }
+
     /// Send a request to the indexer using the query parameters.
-    async fn request(&self, param_str: String) -> Result<Bytes, reqwest::Error> {
+    async fn request(&self, pairs: &[QueryPair]) -> Result<Bytes, ClientError> {
         let span = span!(parent: &self.client_span, Level::INFO, "client request");
         let _enter = span.enter();
 
-        // Construct the url
-        let url = format!("{}?apikey={}{}", self.base_url, self.api_key, param_str);
-        debug!("Url: {}", url);
+        let url = self.url(pairs)?;
+        debug!("Url: {}", redact(url.as_str()));
+
+        let bytes = self.get(url.as_str()).await?;
+
+        // Indexers respond with errors using a successful status code.
+        self.check_api_error(&bytes)?;
 
-        self.http.get(url).send().await?.error_for_status()?.bytes().await
+        Ok(bytes)
+    }
+
+    /// Download a file from the indexer, like the .torrent file of a search result.
+    pub async fn download(&self, url: &str) -> Result<Bytes, ClientError> {
+        let span = span!(parent: &self.client_span, Level::INFO, "client download");
+        let _enter = span.enter();
+
+        debug!("Downloading: {}", redact(url));
+
+        let bytes = self.get(url).await?;
+
+        // Some indexers respond with an error instead of the file, like when
+        // the download limit is reached.
+        self.check_api_error(&bytes)?;
+
+        Ok(bytes)
     }
 
     /// Request the capabilities of the indexer and return them.
-    pub async fn request_capabilities(&self) -> Result<Capabilities, reqwest::Error> {
-        let params = TorznabFunction::Capabilities.to_params();
+    pub async fn request_capabilities(&self) -> Result<Capabilities, ClientError> {
+        let pairs = TorznabFunction::Capabilities.to_pairs();
 
-        let res = self.request(params).await?;
-        let str_res = String::from_utf8(res.as_ref().to_vec()).unwrap(); // TODO Handle
+        let res = self.request(&pairs).await?;
+        let str_res = std::str::from_utf8(res.as_ref())?;
 
-        let cap: Capabilities = quick_xml::de::from_str(&str_res).unwrap();
+        let cap: Capabilities = quick_xml::de::from_str(str_res)?;
 
         Ok(cap)
     }
 
     /// Request and store the capabilities of the indexer in the struct.
-    pub async fn store_capabilities(&mut self) -> Result<&Capabilities, reqwest::Error> {
+    pub async fn store_capabilities(&mut self) -> Result<&Capabilities, ClientError> {
         self.capabilities = self.request_capabilities().await?;
         Ok(&self.capabilities)
     }
 
     /// Search for torrents.
-    pub async fn search(&self, func: SearchFunction, generic_params: GenericSearchParameters) -> Result<(), ClientError> {
-        let param_str = format!("{}{}", func.to_params(), generic_params.to_params());
+    pub async fn search(&self, func: SearchFunction, mut generic_params: GenericSearchParameters) -> Result<SearchResponse, ClientError> {
+        generic_params.limit = generic_params.limit.map(|limit| self.capabilities.clamp_limit(limit));
 
-        let bytes = self.request(param_str).await?;
+        let pairs = TorznabFunction::SearchFunction(generic_params, func).to_pairs();
+
+        let bytes = self.request(&pairs).await?;
         let reader = bytes.reader();
 
-        let channel = Channel::read_from(reader).unwrap(); // TODO: handle
-        let items = channel.into_items();
+        let channel = Channel::read_from(reader)?;
+        let response = SearchResponse::from_channel(&channel)?;
+
+        debug!("Found {} results", response.len());
 
-        let torrents: Vec<TorrentResult> = items.iter()
-            .map(TorrentResult::from_item)
-            .collect::<Result<Vec<TorrentResult>, super::ResultError>>()?;
+        Ok(response)
+    }
+
+    /// Search for torrents, requesting pages with an increasing offset.
+    ///
+    /// Pages are requested until the indexer returns a short page, the total
+    /// amount of results is reached, or `max_results` results were returned.
+    /// Results that were already returned by an earlier page are skipped.
+    pub fn search_paginated(&self, func: SearchFunction, generic_params: GenericSearchParameters, max_results: usize)
+        -> impl Stream<Item = Result<TorrentResult, ClientError>> + '_
+    {
+        let limits = self.capabilities.limits;
+        let page_size = generic_params.limit
+            .or_else(|| limits.and_then(|l| l.default).map(|d| d as i32))
+            .unwrap_or(DEFAULT_PAGE_SIZE);
+        let page_size = self.capabilities.clamp_limit(page_size).max(1);
+
+        let state = PageState {
+            offset: generic_params.offset.unwrap_or(0),
+            returned: 0,
+            seen: HashSet::new(),
+            done: false,
+        };
 
-        debug!("Found results: {:?}", torrents);
+        stream::try_unfold(state, move |mut state| {
+            let func = func.clone();
+            let mut params = generic_params.clone();
 
-        //Torrent::from
+            async move {
+                if state.done || state.returned >= max_results {
+                    return Ok::<_, ClientError>(None);
+                }
 
-        Ok(())
+                params.offset = Some(state.offset);
+                params.limit = Some(page_size);
+
+                let response = self.search(func, params).await?;
+                let count = response.len();
+                state.offset += count as i32;
+
+                let results: Vec<TorrentResult> = response.results.into_iter()
+                    .filter(|result| state.seen.insert(result_key(result)))
+                    .take(max_results - state.returned)
+                    .collect();
+                state.returned += results.len();
+
+                // Indexers that ignore the offset keep returning the same page.
+                let reached_total = response.total.is_some_and(|total| state.offset >= total as i32);
+                state.done = count < page_size as usize || reached_total || results.is_empty();
+
+                debug!("Got page with {} results, {} new (offset {})", count, results.len(), state.offset);
+
+                Ok(Some((stream::iter(results.into_iter().map(Ok)), state)))
+            }
+        })
+        .try_flatten()
     }
-}
\ No newline at end of file
+}
+
+/// The key used to find duplicate results between pages.
+fn result_key(result: &TorrentResult) -> String {
+    result.attributes.infohash.clone()
+        .or_else(|| result.guid.clone())
+        .unwrap_or_else(|| result.link.clone())
+}
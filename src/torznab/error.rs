@@ -1,11 +1,184 @@
+use std::fmt;
+
+use quick_xml::Reader;
+use quick_xml::events::Event;
+
+/// The error codes of the Torznab/Newznab api.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ApiErrorKind {
+    /// 100
+    IncorrectCredentials,
+    /// 101
+    AccountSuspended,
+    /// 102
+    InsufficientPrivileges,
+    /// 103
+    RegistrationDenied,
+    /// 104
+    RegistrationsClosed,
+    /// 105
+    EmailTaken,
+    /// 106
+    EmailBadFormat,
+    /// 107
+    RegistrationFailed,
+    /// 200
+    MissingParameter,
+    /// 201
+    IncorrectParameter,
+    /// 202
+    NoSuchFunction,
+    /// 203
+    FunctionNotAvailable,
+    /// 300
+    NoSuchItem,
+    /// 500
+    RequestLimitReached,
+    /// 501
+    DownloadLimitReached,
+    /// 900
+    Unknown,
+    /// 910
+    ApiDisabled,
+    /// A code that isn't in the specification.
+    Other,
+}
+
+impl From<u32> for ApiErrorKind {
+    fn from(code: u32) -> Self {
+        match code {
+            100 => ApiErrorKind::IncorrectCredentials,
+            101 => ApiErrorKind::AccountSuspended,
+            102 => ApiErrorKind::InsufficientPrivileges,
+            103 => ApiErrorKind::RegistrationDenied,
+            104 => ApiErrorKind::RegistrationsClosed,
+            105 => ApiErrorKind::EmailTaken,
+            106 => ApiErrorKind::EmailBadFormat,
+            107 => ApiErrorKind::RegistrationFailed,
+            200 => ApiErrorKind::MissingParameter,
+            201 => ApiErrorKind::IncorrectParameter,
+            202 => ApiErrorKind::NoSuchFunction,
+            203 => ApiErrorKind::FunctionNotAvailable,
+            300 => ApiErrorKind::NoSuchItem,
+            500 => ApiErrorKind::RequestLimitReached,
+            501 => ApiErrorKind::DownloadLimitReached,
+            900 => ApiErrorKind::Unknown,
+            910 => ApiErrorKind::ApiDisabled,
+            _ => ApiErrorKind::Other,
+        }
+    }
+}
+
+/// An `<error code="..." description="..."/>` response from the indexer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApiError {
+    pub kind: ApiErrorKind,
+    pub code: u32,
+    pub description: String,
+}
+
+impl ApiError {
+    pub fn new(code: u32, description: String) -> Self {
+        ApiError {
+            kind: code.into(),
+            code,
+            description,
+        }
+    }
+
+    /// Parse the error from a response body.
+    ///
+    /// Returns `None` if the root element of the body isn't an error.
+    pub fn from_body(body: &str) -> Option<Self> {
+        let mut reader = Reader::from_str(body);
+        let mut buf = Vec::new();
+
+        // Find the root element, skipping the declaration and comments.
+        loop {
+            match reader.read_event(&mut buf).ok()? {
+                Event::Start(e) | Event::Empty(e) => {
+                    if e.local_name() != b"error" {
+                        return None;
+                    }
+
+                    let mut code = None;
+                    let mut description = String::new();
+                    for attr in e.attributes().flatten() {
+                        let value = attr.unescape_and_decode_value(&reader).ok()?;
+                        match attr.key {
+                            b"code" => code = value.trim().parse().ok(),
+                            b"description" => description = value,
+                            _ => {},
+                        }
+                    }
+
+                    return Some(ApiError::new(code.unwrap_or(900), description));
+                },
+                Event::Eof => return None,
+                _ => {},
+            }
+
+            buf.clear();
+        }
+    }
+
+    /// Returns true if the indexer won't answer any more requests during this run.
+    pub fn is_fatal(&self) -> bool {
+        matches!(self.kind, ApiErrorKind::IncorrectCredentials
+            | ApiErrorKind::AccountSuspended
+            | ApiErrorKind::InsufficientPrivileges
+            | ApiErrorKind::RequestLimitReached
+            | ApiErrorKind::ApiDisabled)
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "indexer returned error {}: {}", self.code, self.description)
+    }
+}
+
 #[derive(Debug)]
 pub enum ClientError {
     HttpError(reqwest::Error),
-    SearchResultError(super::ResultError)
+    SearchResultError(super::ResultError),
+    /// The indexer kept failing and was disabled for the rest of the run.
+    IndexerDisabled(String),
+    /// The indexer responded with a Torznab error.
+    ApiError(ApiError),
+    /// The capabilities response couldn't be parsed.
+    XmlError(quick_xml::DeError),
+    /// The search response couldn't be parsed.
+    RssError(rss::Error),
+    InvalidUtf8(std::str::Utf8Error),
+    /// The url of the indexer couldn't be parsed.
+    InvalidUrl(url::ParseError),
+}
+
+impl fmt::Display for ClientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClientError::HttpError(e) => write!(f, "http error: {}", e),
+            ClientError::SearchResultError(e) => write!(f, "invalid search result: {:?}", e),
+            ClientError::IndexerDisabled(reason) => write!(f, "indexer is disabled: {}", reason),
+            ClientError::ApiError(e) => write!(f, "{}", e),
+            ClientError::XmlError(e) => write!(f, "invalid xml: {}", e),
+            ClientError::RssError(e) => write!(f, "invalid rss: {}", e),
+            ClientError::InvalidUtf8(e) => write!(f, "response isn't valid utf-8: {}", e),
+            ClientError::InvalidUrl(e) => write!(f, "invalid url: {}", e),
+        }
+    }
 }
 
+impl std::error::Error for ClientError {}
+
 impl From<reqwest::Error> for ClientError {
-    fn from(e: reqwest::Error) -> Self {
+    fn from(mut e: reqwest::Error) -> Self {
+        // The url is part of the display of the error.
+        if let Some(url) = e.url_mut() {
+            crate::redact::redact_url(url);
+        }
+
         ClientError::HttpError(e)
     }
 }
@@ -14,4 +187,34 @@ impl From<super::ResultError> for ClientError {
     fn from(e: super::ResultError) -> Self {
         ClientError::SearchResultError(e)
     }
-}
\ No newline at end of file
+}
+
+impl From<ApiError> for ClientError {
+    fn from(e: ApiError) -> Self {
+        ClientError::ApiError(e)
+    }
+}
+
+impl From<quick_xml::DeError> for ClientError {
+    fn from(e: quick_xml::DeError) -> Self {
+        ClientError::XmlError(e)
+    }
+}
+
+impl From<rss::Error> for ClientError {
+    fn from(e: rss::Error) -> Self {
+        ClientError::RssError(e)
+    }
+}
+
+impl From<std::str::Utf8Error> for ClientError {
+    fn from(e: std::str::Utf8Error) -> Self {
+        ClientError::InvalidUtf8(e)
+    }
+}
+
+impl From<url::ParseError> for ClientError {
+    fn from(e: url::ParseError) -> Self {
+        ClientError::InvalidUrl(e)
+    }
+}
@@ -1,6 +1,6 @@
 use super::search_parameters::*;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum SearchFunction {
     /// Free text search query.
     Search,
@@ -8,13 +8,10 @@ pub enum SearchFunction {
     TVSearch(TVSearchParameters),
     /// Search query with movie specific query params and filtering.
     MovieSearch(MovieSearchParameters),
-
-    // TODO
-
     /// Search query with music specific query params and filtering.
-    MusicSearch,
+    MusicSearch(MusicSearchParameters),
     /// Search query with book specific query params and filtering.
-    BookSearch,
+    BookSearch(BookSearchParameters),
 }
 
 impl SearchFunction {
@@ -23,29 +20,29 @@ impl SearchFunction {
             SearchFunction::Search => "search",
             SearchFunction::TVSearch(_) => "tvsearch",
             SearchFunction::MovieSearch(_) => "movie",
-            SearchFunction::MusicSearch => "music",
-            SearchFunction::BookSearch => "book",
+            SearchFunction::MusicSearch(_) => "music",
+            SearchFunction::BookSearch(_) => "book",
         }
     }
 
-    pub fn to_params(&self) -> String {
-        let mut params = String::new();
+    pub fn to_pairs(&self) -> Vec<QueryPair> {
+        let mut pairs = vec![("t", self.to_function_str().to_string())];
 
-        params.push_str(&format!("&t={}", self.to_function_str()));
-
-        // Concatenate the params of the search function.
+        // Add the params of the search function.
         match self {
-            SearchFunction::Search => {},//params.push_str(&s),
-            SearchFunction::TVSearch(p) => params.push_str(&p.to_params()),
-            SearchFunction::MovieSearch(p) => params.push_str(&p.to_params()),
-            _ => panic!("Not implemented!"), // TODO
+            SearchFunction::Search => {},
+            SearchFunction::TVSearch(p) => pairs.extend(p.to_pairs()),
+            SearchFunction::MovieSearch(p) => pairs.extend(p.to_pairs()),
+            SearchFunction::MusicSearch(p) => pairs.extend(p.to_pairs()),
+            SearchFunction::BookSearch(p) => pairs.extend(p.to_pairs()),
         }
 
-        params
+        pairs
     }
 }
 
 #[derive(Debug)]
+#[allow(clippy::large_enum_variant)]
 pub enum TorznabFunction {
     /// Returns the capabilities of the api.
     Capabilities,
@@ -83,18 +80,14 @@ impl TorznabFunction {
         }
     }
 
-    pub fn to_params(&self) -> String {
-        let mut params = String::new();
-
-        // Concatenate the params of the search function.
+    pub fn to_pairs(&self) -> Vec<QueryPair> {
         match self {
             TorznabFunction::SearchFunction(p, func) => {
-                params.push_str(&p.to_params());
-                params.push_str(&func.to_params());
+                let mut pairs = func.to_pairs();
+                pairs.extend(p.to_pairs());
+                pairs
             },
-            _ => params.push_str(&format!("&t={}", self.to_function_str())),
+            _ => vec![("t", self.to_function_str().to_string())],
         }
-
-        params
     }
 }
\ No newline at end of file
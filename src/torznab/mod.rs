@@ -13,5 +13,14 @@ pub use error::*;
 pub mod client;
 pub use client::*;
 
+pub mod rate_limiter;
+pub use rate_limiter::*;
+
+pub mod retry;
+pub use retry::*;
+
 pub mod torrent_result;
-pub use torrent_result::*;
\ No newline at end of file
+pub use torrent_result::*;
+
+pub mod search_response;
+pub use search_response::*;
\ No newline at end of file
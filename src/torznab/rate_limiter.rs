@@ -0,0 +1,162 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+use tokio::time::Instant;
+
+/// The rate limit of an indexer.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct RateLimit {
+    /// The amount of requests that can be sent in each interval.
+    pub requests: u32,
+    /// The length of the interval in seconds.
+    pub interval_secs: u64,
+    /// The minimum time between two requests in milliseconds.
+    pub delay_ms: Option<u64>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    last_refill: Instant,
+    last_request: Option<Instant>,
+}
+
+/// A token bucket limiting the requests sent to an indexer.
+///
+/// The bucket starts full, so a burst of `requests` can be sent right away.
+#[derive(Debug)]
+pub struct RateLimiter {
+    capacity: f64,
+    /// Tokens that are added each second.
+    refill_rate: f64,
+    delay: Duration,
+    bucket: Mutex<Bucket>,
+}
+
+impl RateLimiter {
+    pub fn new(limit: &RateLimit) -> Self {
+        let capacity = limit.requests.max(1) as f64;
+        let interval = Duration::from_secs(limit.interval_secs).as_secs_f64();
+
+        RateLimiter {
+            capacity,
+            refill_rate: if interval > 0.0 { capacity / interval } else { f64::INFINITY },
+            delay: Duration::from_millis(limit.delay_ms.unwrap_or(0)),
+            bucket: Mutex::new(Bucket {
+                tokens: capacity,
+                last_refill: Instant::now(),
+                last_request: None,
+            }),
+        }
+    }
+
+    /// Wait until a request can be sent.
+    pub async fn acquire(&self) {
+        // The lock is held while waiting so requests are let through in order.
+        let mut bucket = self.bucket.lock().await;
+
+        self.refill(&mut bucket);
+        if bucket.tokens < 1.0 {
+            let missing = 1.0 - bucket.tokens;
+            tokio::time::sleep(Duration::from_secs_f64(missing / self.refill_rate)).await;
+            self.refill(&mut bucket);
+        }
+
+        if let Some(last_request) = bucket.last_request {
+            tokio::time::sleep_until(last_request + self.delay).await;
+        }
+
+        bucket.tokens = (bucket.tokens - 1.0).max(0.0);
+        bucket.last_request = Some(Instant::now());
+    }
+
+    fn refill(&self, bucket: &mut Bucket) {
+        let now = Instant::now();
+        let elapsed = now.duration_since(bucket.last_refill).as_secs_f64();
+
+        // An interval of zero doesn't limit the amount of requests.
+        bucket.tokens = if self.refill_rate.is_finite() {
+            (bucket.tokens + elapsed * self.refill_rate).min(self.capacity)
+        } else {
+            self.capacity
+        };
+        bucket.last_refill = now;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limiter(requests: u32, interval_secs: u64, delay_ms: Option<u64>) -> RateLimiter {
+        RateLimiter::new(&RateLimit { requests, interval_secs, delay_ms })
+    }
+
+    /// How long it takes to acquire a token.
+    async fn wait(limiter: &RateLimiter) -> Duration {
+        let start = Instant::now();
+        limiter.acquire().await;
+
+        start.elapsed()
+    }
+
+    fn assert_about(actual: Duration, expected_ms: u64) {
+        let expected = Duration::from_millis(expected_ms);
+        assert!(actual >= expected && actual <= expected + Duration::from_millis(5), "{:?} isn't about {:?}", actual, expected);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn sends_a_burst_right_away() {
+        let limiter = limiter(3, 10, None);
+
+        for _ in 0..3 {
+            assert_eq!(wait(&limiter).await, Duration::ZERO);
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn waits_for_a_token_when_empty() {
+        let limiter = limiter(2, 10, None);
+        wait(&limiter).await;
+        wait(&limiter).await;
+
+        // A token is added every 5 seconds.
+        assert_about(wait(&limiter).await, 5_000);
+        assert_about(wait(&limiter).await, 5_000);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn refills_over_time_up_to_the_capacity() {
+        let limiter = limiter(2, 10, None);
+        wait(&limiter).await;
+        wait(&limiter).await;
+
+        tokio::time::advance(Duration::from_secs(5)).await;
+        assert_eq!(wait(&limiter).await, Duration::ZERO);
+
+        // Waiting longer than the interval doesn't allow a bigger burst.
+        tokio::time::advance(Duration::from_secs(60)).await;
+        assert_eq!(wait(&limiter).await, Duration::ZERO);
+        assert_eq!(wait(&limiter).await, Duration::ZERO);
+        assert_about(wait(&limiter).await, 5_000);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn keeps_the_delay_between_requests() {
+        let limiter = limiter(10, 1, Some(500));
+
+        assert_eq!(wait(&limiter).await, Duration::ZERO);
+        assert_about(wait(&limiter).await, 500);
+        assert_about(wait(&limiter).await, 500);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn an_interval_of_zero_only_keeps_the_delay() {
+        let limiter = limiter(1, 0, None);
+
+        for _ in 0..10 {
+            assert_eq!(wait(&limiter).await, Duration::ZERO);
+        }
+    }
+}
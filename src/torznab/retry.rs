@@ -0,0 +1,131 @@
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use rand::Rng;
+use reqwest::StatusCode;
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use serde::{Deserialize, Serialize};
+
+/// How failed requests to an indexer are retried.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct RetryPolicy {
+    /// The amount of times a request is retried before giving up.
+    pub max_retries: u32,
+    /// The delay before the first retry in milliseconds. It doubles on every retry.
+    pub base_delay_ms: u64,
+    /// The longest delay between two retries in seconds. A longer `Retry-After`
+    /// from the indexer is still waited for.
+    pub max_delay_secs: u64,
+    /// The amount of failed requests in a row, from server errors or connection
+    /// problems, before the indexer is disabled for the rest of the run.
+    pub disable_after: u32,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_retries: 3,
+            base_delay_ms: 1000,
+            max_delay_secs: 60,
+            disable_after: 5,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// The delay before retrying, with `attempt` starting at 0.
+    ///
+    /// A `Retry-After` delay from the indexer is honored, even when it's longer
+    /// than `max_delay_secs`, otherwise the delay grows exponentially. Jitter
+    /// is added so concurrent tasks don't retry at the same moment.
+    pub fn delay(&self, attempt: u32, retry_after: Option<Duration>) -> Duration {
+        let base = Duration::from_millis(self.base_delay_ms);
+        let max = Duration::from_secs(self.max_delay_secs);
+
+        match retry_after {
+            Some(retry_after) => {
+                let jitter = rand::thread_rng().gen_range(0..=self.base_delay_ms);
+                retry_after + Duration::from_millis(jitter)
+            },
+            None => {
+                let delay = base.saturating_mul(2u32.saturating_pow(attempt)).min(max);
+                let half = delay.as_millis() as u64 / 2;
+                let jitter = rand::thread_rng().gen_range(0..=half);
+
+                Duration::from_millis(half + jitter)
+            },
+        }
+    }
+}
+
+/// Returns true if the status means that the request may succeed later.
+pub fn is_retryable_status(status: StatusCode) -> bool {
+    matches!(status, StatusCode::TOO_MANY_REQUESTS
+        | StatusCode::SERVICE_UNAVAILABLE
+        | StatusCode::BAD_GATEWAY
+        | StatusCode::GATEWAY_TIMEOUT)
+}
+
+/// Returns true if the status means that the indexer won't accept any request.
+pub fn is_fatal_status(status: StatusCode) -> bool {
+    matches!(status, StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN)
+}
+
+/// Returns true if the request failed before getting the whole response and may succeed later.
+pub fn is_retryable_error(error: &reqwest::Error) -> bool {
+    error.is_timeout() || error.is_connect() || error.is_body()
+}
+
+/// Parse the `Retry-After` header, which is either an amount of seconds or a date.
+pub fn parse_retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+
+    let date = DateTime::parse_from_rfc2822(value).ok()?;
+    let delay = date.with_timezone(&Utc) - Utc::now();
+
+    // A date in the past means the request can be retried right away.
+    Some(delay.to_std().unwrap_or_default())
+}
+
+/// Keeps track of failing requests and disables an indexer that keeps failing.
+#[derive(Debug, Default)]
+pub struct IndexerHealth {
+    consecutive_failures: AtomicU32,
+    disabled_reason: Mutex<Option<String>>,
+}
+
+impl IndexerHealth {
+    pub fn record_success(&self) {
+        self.consecutive_failures.store(0, Ordering::SeqCst);
+    }
+
+    /// Record a failed request, disabling the indexer after `disable_after`
+    /// failures in a row.
+    pub fn record_failure(&self, reason: String, disable_after: u32) {
+        let failures = self.consecutive_failures.fetch_add(1, Ordering::SeqCst) + 1;
+
+        if failures >= disable_after {
+            self.disable(format!("{} failed requests in a row, last error: {}", failures, reason));
+        }
+    }
+
+    /// Disable the indexer for the rest of the run.
+    pub fn disable(&self, reason: String) {
+        let mut disabled = self.disabled_reason.lock().unwrap();
+        if disabled.is_none() {
+            *disabled = Some(reason);
+        }
+    }
+
+    /// The reason the indexer was disabled, if it was.
+    pub fn disabled_reason(&self) -> Option<String> {
+        self.disabled_reason.lock().unwrap().clone()
+    }
+}
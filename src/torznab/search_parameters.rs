@@ -1,4 +1,10 @@
-#[derive(Debug)]
+use std::fmt;
+use std::str::FromStr;
+
+/// A key and value of the query string of a request.
+pub type QueryPair = (&'static str, String);
+
+#[derive(Debug, Clone)]
 pub struct GenericSearchParameters {
     /// The string search query.
     pub query: Option<String>,
@@ -15,44 +21,41 @@ pub struct GenericSearchParameters {
 }
 
 impl GenericSearchParameters {
-    /// Convert the search parameters to a query string.
-    /// This will be prefixed with "&"
-    pub fn to_params(&self) -> String {
-        let mut params = String::new();
+    /// Convert the search parameters to query pairs.
+    pub fn to_pairs(&self) -> Vec<QueryPair> {
+        let mut pairs = Vec::new();
 
         if let Some(query) = &self.query {
-            let encoded = urlencoding::encode(query);
-            params.push_str(&format!("&q={}", encoded));
+            pairs.push(("q", query.clone()));
         }
 
-        if self.categories.len() > 0 {
-            params.push_str(&format!("&cat={}", 
-                self.categories.iter()
-                    .map(|i| i.to_string())
-                    .collect::<Vec<_>>()
+        if !self.categories.is_empty() {
+            pairs.push(("cat", self.categories.iter()
+                .map(|i| i.to_string())
+                .collect::<Vec<_>>()
                 .join(",")));
         }
 
-        if self.attributes.len() > 0 {
-            params.push_str(&format!("&attrs={}", self.attributes.join(",")));
+        if !self.attributes.is_empty() {
+            pairs.push(("attrs", self.attributes.join(",")));
         }
 
         if let Some(extended) = &self.extended {
             // Convert the boolean to an integer.
             let i = if *extended { 1 } else { 0 };
 
-            params.push_str(&format!("&extended={}", i));
+            pairs.push(("extended", i.to_string()));
         }
 
         if let Some(offset) = &self.offset {
-            params.push_str(&format!("&offset={}", offset));
+            pairs.push(("offset", offset.to_string()));
         }
 
         if let Some(limit) = &self.limit {
-            params.push_str(&format!("&limit={}", limit));
+            pairs.push(("limit", limit.to_string()));
         }
 
-        params
+        pairs
     }
 }
 
@@ -119,7 +122,13 @@ impl GenericSearchParametersBuilder {
     }
 }
 
-#[derive(Debug)]
+impl Default for GenericSearchParametersBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct TVSearchParameters {
     // idk what this is tbh
     pub rid: Option<u32>,
@@ -134,30 +143,30 @@ pub struct TVSearchParameters {
 }
 
 impl TVSearchParameters {
-    pub fn to_params(&self) -> String {
-        let mut params = String::new();
+    pub fn to_pairs(&self) -> Vec<QueryPair> {
+        let mut pairs = Vec::new();
 
         if let Some(rid) = &self.rid {
-            params.push_str(&format!("&rid={}", rid));
+            pairs.push(("rid", rid.to_string()));
         }
 
         if let Some(tvdb_id) = &self.tvdb_id {
-            params.push_str(&format!("&tvdbid={}", tvdb_id));
+            pairs.push(("tvdbid", tvdb_id.to_string()));
         }
 
         if let Some(tvmaze_id) = &self.tvmaze_id {
-            params.push_str(&format!("&tvmazeid={}", tvmaze_id));
+            pairs.push(("tvmazeid", tvmaze_id.to_string()));
         }
 
         if let Some(season) = &self.season {
-            params.push_str(&format!("&season={}", season));
+            pairs.push(("season", season.to_string()));
         }
 
         if let Some(episode) = &self.episode {
-            params.push_str(&format!("&ep={}", episode));
+            pairs.push(("ep", episode.to_string()));
         }
 
-        params
+        pairs
     }
 }
 
@@ -208,21 +217,77 @@ impl TVSearchParametersBuilder {
     }
 }
 
-#[derive(Debug)]
+impl Default for TVSearchParametersBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// An IMDB id, like `tt0133093`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ImdbId(String);
+
+impl ImdbId {
+    /// The numeric part of the id, without the `tt` prefix.
+    pub fn number(&self) -> &str {
+        &self.0[2..]
+    }
+}
+
+impl FromStr for ImdbId {
+    type Err = String;
+
+    /// Parse an id with or without the `tt` prefix, padding it to 7 digits.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let digits = s.strip_prefix("tt").unwrap_or(s);
+
+        if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+            return Err(format!("Invalid IMDB id: {}", s));
+        }
+
+        Ok(ImdbId(format!("tt{:0>7}", digits)))
+    }
+}
+
+impl fmt::Display for ImdbId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct MovieSearchParameters {
     /// Id of the movie on IMDB.
-    pub imdb_id: Option<u32>,
+    pub imdb_id: Option<ImdbId>,
+    /// Id of the movie on TMDB.
+    pub tmdb_id: Option<u32>,
+    /// Release year of the movie.
+    pub year: Option<u16>,
+    pub genre: Option<String>,
 }
 
 impl MovieSearchParameters {
-    pub fn to_params(&self) -> String {
-        let mut params = String::new();
+    pub fn to_pairs(&self) -> Vec<QueryPair> {
+        let mut pairs = Vec::new();
 
         if let Some(imdb_id) = &self.imdb_id {
-            params.push_str(&format!("&imdbid={}", imdb_id));
+            pairs.push(("imdbid", imdb_id.to_string()));
+        }
+
+        if let Some(tmdb_id) = &self.tmdb_id {
+            pairs.push(("tmdbid", tmdb_id.to_string()));
+        }
+
+        if let Some(year) = &self.year {
+            pairs.push(("year", year.to_string()));
+        }
+
+        if let Some(genre) = &self.genre {
+            pairs.push(("genre", genre.clone()));
         }
 
-        params
+        pairs
     }
 }
 
@@ -235,16 +300,266 @@ impl MovieSearchParametersBuilder {
         MovieSearchParametersBuilder {
             params: MovieSearchParameters {
                 imdb_id: None,
+                tmdb_id: None,
+                year: None,
+                genre: None,
             },
         }
     }
 
-    pub fn imdb_id(mut self, imdb_id: u32) -> MovieSearchParametersBuilder {
+    pub fn imdb_id(mut self, imdb_id: ImdbId) -> MovieSearchParametersBuilder {
         self.params.imdb_id = Some(imdb_id);
         self
     }
 
+    pub fn tmdb_id(mut self, tmdb_id: u32) -> MovieSearchParametersBuilder {
+        self.params.tmdb_id = Some(tmdb_id);
+        self
+    }
+
+    pub fn year(mut self, year: u16) -> MovieSearchParametersBuilder {
+        self.params.year = Some(year);
+        self
+    }
+
+    pub fn genre(mut self, genre: String) -> MovieSearchParametersBuilder {
+        self.params.genre = Some(genre);
+        self
+    }
+
     pub fn build(self) -> MovieSearchParameters {
         self.params
     }
-}
\ No newline at end of file
+}
+
+impl Default for MovieSearchParametersBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MusicSearchParameters {
+    pub artist: Option<String>,
+    pub album: Option<String>,
+    /// The record label of the release.
+    pub label: Option<String>,
+    pub track: Option<String>,
+    /// Release year of the album.
+    pub year: Option<u16>,
+    pub genre: Option<String>,
+}
+
+impl MusicSearchParameters {
+    pub fn to_pairs(&self) -> Vec<QueryPair> {
+        let mut pairs = Vec::new();
+
+        if let Some(artist) = &self.artist {
+            pairs.push(("artist", artist.clone()));
+        }
+
+        if let Some(album) = &self.album {
+            pairs.push(("album", album.clone()));
+        }
+
+        if let Some(label) = &self.label {
+            pairs.push(("label", label.clone()));
+        }
+
+        if let Some(track) = &self.track {
+            pairs.push(("track", track.clone()));
+        }
+
+        if let Some(year) = &self.year {
+            pairs.push(("year", year.to_string()));
+        }
+
+        if let Some(genre) = &self.genre {
+            pairs.push(("genre", genre.clone()));
+        }
+
+        pairs
+    }
+}
+
+pub struct MusicSearchParametersBuilder {
+    params: MusicSearchParameters,
+}
+
+impl MusicSearchParametersBuilder {
+    pub fn new() -> MusicSearchParametersBuilder {
+        MusicSearchParametersBuilder {
+            params: MusicSearchParameters {
+                artist: None,
+                album: None,
+                label: None,
+                track: None,
+                year: None,
+                genre: None,
+            },
+        }
+    }
+
+    pub fn artist(mut self, artist: String) -> MusicSearchParametersBuilder {
+        self.params.artist = Some(artist);
+        self
+    }
+
+    pub fn album(mut self, album: String) -> MusicSearchParametersBuilder {
+        self.params.album = Some(album);
+        self
+    }
+
+    pub fn label(mut self, label: String) -> MusicSearchParametersBuilder {
+        self.params.label = Some(label);
+        self
+    }
+
+    pub fn track(mut self, track: String) -> MusicSearchParametersBuilder {
+        self.params.track = Some(track);
+        self
+    }
+
+    pub fn year(mut self, year: u16) -> MusicSearchParametersBuilder {
+        self.params.year = Some(year);
+        self
+    }
+
+    pub fn genre(mut self, genre: String) -> MusicSearchParametersBuilder {
+        self.params.genre = Some(genre);
+        self
+    }
+
+    pub fn build(self) -> MusicSearchParameters {
+        self.params
+    }
+}
+
+impl Default for MusicSearchParametersBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct BookSearchParameters {
+    pub author: Option<String>,
+    pub title: Option<String>,
+    pub publisher: Option<String>,
+    /// Publication year of the book.
+    pub year: Option<u16>,
+    pub genre: Option<String>,
+}
+
+impl BookSearchParameters {
+    pub fn to_pairs(&self) -> Vec<QueryPair> {
+        let mut pairs = Vec::new();
+
+        if let Some(author) = &self.author {
+            pairs.push(("author", author.clone()));
+        }
+
+        if let Some(title) = &self.title {
+            pairs.push(("title", title.clone()));
+        }
+
+        if let Some(publisher) = &self.publisher {
+            pairs.push(("publisher", publisher.clone()));
+        }
+
+        if let Some(year) = &self.year {
+            pairs.push(("year", year.to_string()));
+        }
+
+        if let Some(genre) = &self.genre {
+            pairs.push(("genre", genre.clone()));
+        }
+
+        pairs
+    }
+}
+
+pub struct BookSearchParametersBuilder {
+    params: BookSearchParameters,
+}
+
+impl BookSearchParametersBuilder {
+    pub fn new() -> BookSearchParametersBuilder {
+        BookSearchParametersBuilder {
+            params: BookSearchParameters {
+                author: None,
+                title: None,
+                publisher: None,
+                year: None,
+                genre: None,
+            },
+        }
+    }
+
+    pub fn author(mut self, author: String) -> BookSearchParametersBuilder {
+        self.params.author = Some(author);
+        self
+    }
+
+    pub fn title(mut self, title: String) -> BookSearchParametersBuilder {
+        self.params.title = Some(title);
+        self
+    }
+
+    pub fn publisher(mut self, publisher: String) -> BookSearchParametersBuilder {
+        self.params.publisher = Some(publisher);
+        self
+    }
+
+    pub fn year(mut self, year: u16) -> BookSearchParametersBuilder {
+        self.params.year = Some(year);
+        self
+    }
+
+    pub fn genre(mut self, genre: String) -> BookSearchParametersBuilder {
+        self.params.genre = Some(genre);
+        self
+    }
+
+    pub fn build(self) -> BookSearchParameters {
+        self.params
+    }
+}
+
+impl Default for BookSearchParametersBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_imdb_ids() {
+        let cases = [
+            ("tt0133093", Ok("tt0133093")),
+            ("0133093", Ok("tt0133093")),
+            ("133093", Ok("tt0133093")),
+            (" tt0133093 ", Ok("tt0133093")),
+            ("tt12345678", Ok("tt12345678")),
+            ("", Err("Invalid IMDB id: ")),
+            ("tt", Err("Invalid IMDB id: tt")),
+            ("tt013309a", Err("Invalid IMDB id: tt013309a")),
+            ("nm0000206", Err("Invalid IMDB id: nm0000206")),
+        ];
+
+        for (id, expected) in cases {
+            let parsed = id.parse::<ImdbId>().map(|id| id.to_string());
+            assert_eq!(parsed, expected.map(str::to_string).map_err(str::to_string), "{}", id);
+        }
+    }
+
+    #[test]
+    fn imdb_id_number_keeps_leading_zeros() {
+        let id: ImdbId = "133093".parse().unwrap();
+
+        assert_eq!(id.number(), "0133093");
+    }
+}
@@ -0,0 +1,46 @@
+use rss::Channel;
+
+use super::{TorrentResult, ResultError};
+
+/// The parsed response of a search request.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchResponse {
+    /// The offset of the first result, from the `newznab:response` element.
+    pub offset: Option<u32>,
+    /// The total amount of results the indexer has for the query, from the
+    /// `newznab:response` element.
+    pub total: Option<u32>,
+    pub results: Vec<TorrentResult>,
+}
+
+impl SearchResponse {
+    pub fn from_channel(channel: &Channel) -> Result<Self, ResultError> {
+        let results = channel.items().iter()
+            .map(TorrentResult::from_item)
+            .collect::<Result<Vec<TorrentResult>, ResultError>>()?;
+
+        // Some indexers use the torznab prefix for the response element.
+        let response = ["newznab", "torznab"].iter()
+            .filter_map(|prefix| channel.extensions().get(*prefix))
+            .filter_map(|ext| ext.get("response"))
+            .find_map(|elements| elements.first());
+
+        let attr = |name: &str| response
+            .and_then(|r| r.attrs.get(name))
+            .and_then(|value| value.parse::<u32>().ok());
+
+        Ok(SearchResponse {
+            offset: attr("offset"),
+            total: attr("total"),
+            results,
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.results.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.results.is_empty()
+    }
+}
@@ -1,4 +1,8 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, FixedOffset};
 use rss::Item;
+use rss::extension::Extension;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ResultError {
@@ -6,37 +10,163 @@ pub enum ResultError {
     MissingLink,
 }
 
+/// The enclosure of a search result, usually pointing at the .torrent file.
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub struct TorrentResult<'a> {
-    name: &'a str,
-    link: &'a str,
-    /* size: u64,
-    categories: Vec<u32>, */
+pub struct Enclosure {
+    pub url: String,
+    /// The length in bytes that the indexer reported. Most indexers put the
+    /// size of the content here instead of the size of the .torrent file.
+    pub length: Option<u64>,
+    pub mime_type: String,
+}
+
+/// The `torznab:attr` elements of a search result.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct TorznabAttributes {
+    pub seeders: Option<u32>,
+    /// Seeders and leechers combined.
+    pub peers: Option<u32>,
+    pub infohash: Option<String>,
+    pub magnet_url: Option<String>,
+    /// Number of files in the torrent.
+    pub files: Option<u32>,
+    /// Number of times the torrent was downloaded.
+    pub grabs: Option<u32>,
+    pub download_volume_factor: Option<f32>,
+    pub upload_volume_factor: Option<f32>,
+    /// The IMDB id, sometimes without the `tt` prefix.
+    pub imdb: Option<String>,
+    pub tvdb_id: Option<u32>,
+    /// Tags like `freeleech` or `internal`, listed in the capabilities of the indexer.
+    pub tags: Vec<String>,
+    /// Any attributes that don't have a field of their own.
+    pub other: HashMap<String, String>,
 }
 
-impl<'a> TorrentResult<'a> {
-    pub fn from_item(item: &'a Item) -> Result<Self, ResultError> {
+impl TorznabAttributes {
+    /// Collect the `torznab:attr` extensions into their fields.
+    ///
+    /// `size` and `category` are skipped since they're stored on the result.
+    fn from_extensions(extensions: &[Extension]) -> Self {
+        let mut attributes = TorznabAttributes::default();
+
+        for (name, value) in extensions.iter().filter_map(attr_name_value) {
+            match name {
+                "seeders" => attributes.seeders = value.parse().ok(),
+                "peers" => attributes.peers = value.parse().ok(),
+                "infohash" => attributes.infohash = Some(value.to_lowercase()),
+                "magneturl" => attributes.magnet_url = Some(value.to_owned()),
+                "files" => attributes.files = value.parse().ok(),
+                "grabs" => attributes.grabs = value.parse().ok(),
+                "downloadvolumefactor" => attributes.download_volume_factor = value.parse().ok(),
+                "uploadvolumefactor" => attributes.upload_volume_factor = value.parse().ok(),
+                "imdb" | "imdbid" => attributes.imdb = Some(value.to_owned()),
+                "tvdbid" => attributes.tvdb_id = value.parse().ok(),
+                "tag" => attributes.tags.push(value.to_owned()),
+                "size" | "category" => {},
+                _ => {
+                    attributes.other.insert(name.to_owned(), value.to_owned());
+                }
+            }
+        }
+
+        attributes
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TorrentResult {
+    pub name: String,
+    /// The download link of the .torrent file.
+    pub link: String,
+    /// Size of the content in bytes.
+    pub size: Option<u64>,
+    /// The newznab category ids of the result.
+    pub categories: Vec<u32>,
+    pub pub_date: Option<DateTime<FixedOffset>>,
+    pub guid: Option<String>,
+    /// Url of the details page of the torrent.
+    pub comments: Option<String>,
+    pub enclosure: Option<Enclosure>,
+    pub attributes: TorznabAttributes,
+}
+
+impl TorrentResult {
+    /// Returns true if the result has the tag.
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.attributes.tags.iter().any(|t| t.eq_ignore_ascii_case(tag))
+    }
+
+    pub fn from_item(item: &Item) -> Result<Self, ResultError> {
         let name = item.title().ok_or(ResultError::MissingTitle)?;
-        let link = item.link().ok_or(ResultError::MissingLink)?;
-        /* let size = item.enclosure().map(|e| e.length().parse::<u64>());
-        let categories = item.categories().ok_or(ResultError::MissingTitle)?; */
+
+        let enclosure = item.enclosure().map(|e| Enclosure {
+            url: e.url().to_owned(),
+            length: e.length().parse().ok(),
+            mime_type: e.mime_type().to_owned(),
+        });
+
+        // Some indexers only put the download link in the enclosure.
+        let link = item.link()
+            .or_else(|| enclosure.as_ref().map(|e| e.url.as_str()))
+            .ok_or(ResultError::MissingLink)?;
+
+        // NZBHydra and other newznab based indexers use the newznab prefix.
+        let torznab_attrs: Vec<Extension> = ["torznab", "newznab"].iter()
+            .filter_map(|prefix| item.extensions().get(*prefix))
+            .filter_map(|ext| ext.get("attr"))
+            .flatten()
+            .cloned()
+            .collect();
+
+        // Prefer the size attribute, falling back to the enclosure length.
+        let size = torznab_attrs.iter()
+            .filter_map(attr_name_value)
+            .find(|(name, _)| *name == "size")
+            .and_then(|(_, value)| value.parse().ok())
+            .or_else(|| enclosure.as_ref().and_then(|e| e.length));
+
+        // Categories can be in both the category elements and the attributes.
+        let mut categories: Vec<u32> = Vec::new();
+        let category_ids = item.categories().iter()
+            .map(|c| c.name())
+            .chain(torznab_attrs.iter()
+                .filter_map(attr_name_value)
+                .filter(|(name, _)| *name == "category")
+                .map(|(_, value)| value))
+            .filter_map(|id| id.trim().parse::<u32>().ok());
+
+        for id in category_ids {
+            if !categories.contains(&id) {
+                categories.push(id);
+            }
+        }
+
+        // Some trackers use ISO 8601 dates instead of RSS dates.
+        let pub_date = item.pub_date()
+            .map(str::trim)
+            .and_then(|date| DateTime::parse_from_rfc2822(date)
+                .or_else(|_| DateTime::parse_from_rfc3339(date))
+                .ok());
 
         Ok(TorrentResult {
-            name,
-            link,
-            /* size,
-            categories, */
+            name: name.to_owned(),
+            link: link.to_owned(),
+            size,
+            categories,
+            pub_date,
+            guid: item.guid().map(|g| g.value().to_owned()),
+            comments: item.comments().map(str::to_owned),
+            enclosure,
+            attributes: TorznabAttributes::from_extensions(&torznab_attrs),
         })
     }
 }
 
-/* impl<'a> From<Item> for TorrentResult<'a> {
-    fn from(item: Item) -> Self {
-        TorrentResult {
-            name: item.title().unwrap(),
-            link: item.link().unwrap(),
-            size: item.size().unwrap(),
-            categories: item.categories().unwrap(),
-        }
-    }
-} */
\ No newline at end of file
+/// Get the name and value of a `torznab:attr` element.
+fn attr_name_value(ext: &Extension) -> Option<(&str, &str)> {
+    let name = ext.attrs.get("name")?;
+    let value = ext.attrs.get("value")?;
+
+    Some((name.as_str(), value.as_str()))
+}
@@ -0,0 +1,124 @@
+use std::path::Path;
+
+use cross_seed::torznab::{Capabilities, Limits, SearchCapability, SupportedParam};
+
+fn caps(name: &str) -> Capabilities {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/caps").join(name);
+    let xml = std::fs::read_to_string(path).unwrap();
+
+    quick_xml::de::from_str(&xml).unwrap()
+}
+
+/// The ids of the categories and their sub categories, in order.
+fn category_ids(caps: &Capabilities) -> Vec<(u32, Vec<u32>)> {
+    caps.categories.categories.iter()
+        .map(|c| (c.id(), c.sub_categories().iter().map(|s| s.id()).collect()))
+        .collect()
+}
+
+#[test]
+fn jackett() {
+    let caps = caps("jackett.xml");
+
+    assert_eq!(caps.server.as_ref().and_then(|s| s.identity()), Some("Jackett".to_string()));
+    assert_eq!(caps.limits, Some(Limits { max: Some(100), default: Some(100) }));
+    assert_eq!(caps.registration, None);
+
+    let searching = &caps.searching_capabilities;
+    assert!(searching.does_support_search(SearchCapability::Search));
+    assert!(searching.does_search_support_param(SearchCapability::TV, SupportedParam::Episode));
+    assert!(searching.does_search_support_param(SearchCapability::Movie, SupportedParam::IMDB));
+    assert!(!searching.does_search_support_param(SearchCapability::Movie, SupportedParam::Year));
+    // Advertised, but not available.
+    assert!(!searching.does_support_search(SearchCapability::Music));
+    assert!(!searching.does_support_search(SearchCapability::Book));
+
+    assert_eq!(category_ids(&caps), [
+        (2000, vec![2030, 2040, 2045]),
+        (5000, vec![5040]),
+        (100001, vec![]),
+        (100002, vec![]),
+    ]);
+    assert_eq!(caps.categories.categories[0].sub_categories()[1].name(), "Movies/HD");
+    assert!(caps.tags.tags.is_empty());
+}
+
+#[test]
+fn prowlarr() {
+    let caps = caps("prowlarr.xml");
+
+    assert_eq!(caps.server.as_ref().and_then(|s| s.title.as_deref()), Some("Prowlarr"));
+
+    let searching = &caps.searching_capabilities;
+    for capability in [SearchCapability::Music, SearchCapability::Audio] {
+        for param in [SupportedParam::Artist, SupportedParam::Album, SupportedParam::Label, SupportedParam::Track] {
+            assert!(searching.does_search_support_param(capability.clone(), param));
+        }
+    }
+    assert!(searching.does_search_support_param(SearchCapability::Book, SupportedParam::Publisher));
+    assert!(searching.does_search_support_param(SearchCapability::TV, SupportedParam::Douban));
+    assert!(searching.does_search_support_param(SearchCapability::Movie, SupportedParam::TMDB));
+
+    assert_eq!(category_ids(&caps), [(3000, vec![3040, 3030]), (7000, vec![7020])]);
+
+    let names: Vec<_> = caps.tags.tags.iter().map(|t| t.name.as_str()).collect();
+    assert_eq!(names, ["freeleech", "internal"]);
+    assert_eq!(caps.tags.get("internal").and_then(|t| t.description.as_deref()), Some("Internal Release"));
+}
+
+#[test]
+fn nzbhydra() {
+    let caps = caps("nzbhydra.xml");
+
+    let server = caps.server.as_ref().unwrap();
+    assert_eq!(server.identity(), Some("NZBHydra 2 4.7.6".to_string()));
+    assert_eq!(server.url.as_deref(), Some("https://github.com/theotherp/nzbhydra2"));
+
+    let registration = caps.registration.as_ref().unwrap();
+    assert!(!registration.available);
+    assert!(registration.open);
+
+    let searching = &caps.searching_capabilities;
+    assert!(searching.does_search_support_param(SearchCapability::TV, SupportedParam::Rid));
+    assert!(searching.does_search_support_param(SearchCapability::Search, SupportedParam::Unknown("minsize".to_string())));
+    assert!(searching.does_search_support_param(SearchCapability::Book, SupportedParam::Author));
+    assert!(!searching.does_support_search(SearchCapability::Audio));
+
+    assert_eq!(category_ids(&caps), [(2000, vec![2040]), (7020, vec![])]);
+}
+
+#[test]
+fn tracker() {
+    let caps = caps("tracker.xml");
+
+    assert_eq!(caps.server, None);
+    assert_eq!(caps.limits, Some(Limits { max: Some(50), default: None }));
+    assert_eq!(caps.clamp_limit(100), 50);
+
+    // The params are trimmed.
+    let searching = &caps.searching_capabilities;
+    assert!(searching.does_search_support_param(SearchCapability::Search, SupportedParam::IMDB));
+    assert!(searching.does_search_support_param(SearchCapability::TV, SupportedParam::Query));
+    assert!(searching.does_search_support_param(SearchCapability::TV, SupportedParam::TVDB));
+    assert!(!searching.does_support_search(SearchCapability::Music));
+
+    assert!(caps.categories.categories.is_empty());
+    assert!(caps.tags.tags.is_empty());
+}
+
+#[test]
+fn sparse() {
+    let caps = caps("sparse.xml");
+
+    // Missing registration attributes mean no.
+    let registration = caps.registration.as_ref().unwrap();
+    assert!(!registration.available);
+    assert!(registration.open);
+
+    // A max above i32::MAX doesn't wrap around.
+    assert_eq!(caps.limits.and_then(|l| l.max), Some(u32::MAX));
+    assert_eq!(caps.clamp_limit(100), 100);
+    assert_eq!(caps.clamp_limit(i32::MAX), i32::MAX);
+
+    assert!(caps.categories.categories.is_empty());
+}
@@ -0,0 +1,139 @@
+//! Builds .torrent files without any data on disk.
+
+use lava_torrent::torrent::v1::Torrent;
+
+/// A minimal bencode value, enough for .torrent files.
+enum Bencode {
+    Int(i64),
+    Bytes(Vec<u8>),
+    List(Vec<Bencode>),
+    /// Keys must be sorted.
+    Dict(Vec<(&'static str, Bencode)>),
+}
+
+impl Bencode {
+    fn str(s: &str) -> Bencode {
+        Bencode::Bytes(s.as_bytes().to_vec())
+    }
+
+    fn encode(&self, out: &mut Vec<u8>) {
+        match self {
+            Bencode::Int(i) => out.extend(format!("i{}e", i).bytes()),
+            Bencode::Bytes(bytes) => {
+                out.extend(format!("{}:", bytes.len()).bytes());
+                out.extend(bytes);
+            },
+            Bencode::List(list) => {
+                out.push(b'l');
+                list.iter().for_each(|value| value.encode(out));
+                out.push(b'e');
+            },
+            Bencode::Dict(dict) => {
+                out.push(b'd');
+                for (key, value) in dict {
+                    Bencode::str(key).encode(out);
+                    value.encode(out);
+                }
+                out.push(b'e');
+            },
+        }
+    }
+}
+
+/// A torrent to build, with the paths of the files relative to the root.
+#[derive(Debug, Clone)]
+pub struct TestTorrent {
+    pub name: String,
+    /// A single file torrent when empty.
+    pub files: Vec<(String, u64)>,
+    /// The size of a single file torrent.
+    pub length: u64,
+    /// Changes the pieces, and so the info hash, of otherwise identical torrents.
+    pub seed: u8,
+    pub comment: Option<String>,
+}
+
+/// The smallest piece length, it grows to keep large torrents small.
+const MIN_PIECE_LENGTH: u64 = 16 * 1024;
+const MAX_PIECES: u64 = 1024;
+
+impl TestTorrent {
+    pub fn single(name: &str, length: u64) -> Self {
+        TestTorrent {
+            name: name.to_string(),
+            files: Vec::new(),
+            length,
+            seed: 0,
+            comment: None,
+        }
+    }
+
+    pub fn multi(name: &str, files: &[(&str, u64)]) -> Self {
+        TestTorrent {
+            name: name.to_string(),
+            files: files.iter().map(|(path, length)| (path.to_string(), *length)).collect(),
+            length: 0,
+            seed: 0,
+            comment: None,
+        }
+    }
+
+    pub fn seed(mut self, seed: u8) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    pub fn comment(mut self, comment: &str) -> Self {
+        self.comment = Some(comment.to_string());
+        self
+    }
+
+    pub fn total_size(&self) -> u64 {
+        match self.files.is_empty() {
+            true => self.length,
+            false => self.files.iter().map(|(_, length)| length).sum(),
+        }
+    }
+
+    pub fn bytes(&self) -> Vec<u8> {
+        let piece_length = self.total_size().div_ceil(MAX_PIECES).next_power_of_two().max(MIN_PIECE_LENGTH);
+        let piece_count = self.total_size().div_ceil(piece_length).max(1);
+        let pieces = (0..piece_count * 20)
+            .map(|i| (i as u8).wrapping_mul(31).wrapping_add(self.seed))
+            .collect();
+
+        let mut info = Vec::new();
+        if self.files.is_empty() {
+            info.push(("length", Bencode::Int(self.length as i64)));
+        } else {
+            let files = self.files.iter()
+                .map(|(path, length)| Bencode::Dict(vec![
+                    ("length", Bencode::Int(*length as i64)),
+                    ("path", Bencode::List(path.split('/').map(Bencode::str).collect())),
+                ]))
+                .collect();
+            info.push(("files", Bencode::List(files)));
+        }
+        info.push(("name", Bencode::str(&self.name)));
+        info.push(("piece length", Bencode::Int(piece_length as i64)));
+        info.push(("pieces", Bencode::Bytes(pieces)));
+
+        let mut torrent = vec![("announce", Bencode::str("http://tracker.invalid/announce"))];
+        if let Some(comment) = &self.comment {
+            torrent.push(("comment", Bencode::str(comment)));
+        }
+        torrent.push(("info", Bencode::Dict(info)));
+
+        let mut out = Vec::new();
+        Bencode::Dict(torrent).encode(&mut out);
+        out
+    }
+
+    pub fn torrent(&self) -> Torrent {
+        Torrent::read_from_bytes(self.bytes()).unwrap()
+    }
+
+    pub fn info_hash(&self) -> String {
+        self.torrent().info_hash()
+    }
+}
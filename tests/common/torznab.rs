@@ -0,0 +1,252 @@
+//! A mock Torznab indexer on top of the stand-in server.
+
+use std::sync::{Arc, Mutex};
+
+use cross_seed::matching::Candidate;
+use cross_seed::torznab::{SearchFunction, TorznabClient};
+use cross_seed::torznab::search_parameters::GenericSearchParametersBuilder;
+
+use super::{Request, Response, StandIn, TestTorrent};
+
+/// The caps of the mock, supporting every search with the usual params.
+pub const DEFAULT_CAPS: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<caps>
+  <server version="1.0" title="Mock" />
+  <limits default="50" max="100" />
+  <registration available="no" open="no" />
+  <searching>
+    <search available="yes" supportedParams="q" />
+    <tv-search available="yes" supportedParams="q,season,ep,imdbid,tvdbid" />
+    <movie-search available="yes" supportedParams="q,imdbid,tmdbid,year" />
+    <music-search available="yes" supportedParams="q,artist,album,year" />
+    <book-search available="yes" supportedParams="q,author,title" />
+  </searching>
+  <categories>
+    <category id="2000" name="Movies">
+      <subcat id="2040" name="Movies/HD" />
+    </category>
+    <category id="5000" name="TV" />
+  </categories>
+  <tags>
+    <tag name="internal" description="Uploader is an internal release group" />
+  </tags>
+</caps>"#;
+
+/// A torrent served by the mock.
+#[derive(Debug, Clone)]
+pub struct MockItem {
+    pub torrent: TestTorrent,
+    /// Overrides the size derived from the torrent.
+    pub size: Option<u64>,
+    pub tags: Vec<String>,
+}
+
+#[derive(Debug)]
+struct MockState {
+    api_key: String,
+    caps: String,
+    items: Vec<MockItem>,
+    /// Returned for every api request when set.
+    error: Option<(u32, String)>,
+    /// Returned instead of the response until it runs out.
+    failures: Vec<u16>,
+    /// The `Retry-After` seconds sent with the failures.
+    retry_after: Option<u64>,
+}
+
+/// A Torznab indexer that serves caps, search results and .torrent files.
+pub struct MockIndexer {
+    pub server: StandIn,
+    state: Arc<Mutex<MockState>>,
+}
+
+impl MockIndexer {
+    pub async fn start(api_key: &str) -> MockIndexer {
+        let state = Arc::new(Mutex::new(MockState {
+            api_key: api_key.to_string(),
+            caps: DEFAULT_CAPS.to_string(),
+            items: Vec::new(),
+            error: None,
+            failures: Vec::new(),
+            retry_after: None,
+        }));
+
+        let server = {
+            let state = Arc::clone(&state);
+            StandIn::start(move |request| handle(&mut state.lock().unwrap(), request)).await
+        };
+
+        MockIndexer { server, state }
+    }
+
+    /// The Torznab url of the indexer.
+    pub fn url(&self) -> String {
+        format!("{}/api", self.server.url)
+    }
+
+    pub fn set_caps(&self, caps: &str) {
+        self.state.lock().unwrap().caps = caps.to_string();
+    }
+
+    pub fn add(&self, torrent: TestTorrent) {
+        self.add_item(MockItem {
+            torrent,
+            size: None,
+            tags: Vec::new(),
+        });
+    }
+
+    pub fn add_item(&self, item: MockItem) {
+        self.state.lock().unwrap().items.push(item);
+    }
+
+    /// Respond to every api request with a Torznab error.
+    pub fn set_error(&self, code: u32, description: &str) {
+        self.state.lock().unwrap().error = Some((code, description.to_string()));
+    }
+
+    /// Respond to the next requests with these status codes.
+    pub fn fail_with(&self, statuses: &[u16]) {
+        self.state.lock().unwrap().failures = statuses.to_vec();
+    }
+
+    /// Send `Retry-After` with the failures.
+    pub fn set_retry_after(&self, seconds: u64) {
+        self.state.lock().unwrap().retry_after = Some(seconds);
+    }
+
+    /// The api requests with a `t` of `function`.
+    pub fn requests(&self, function: &str) -> Vec<Request> {
+        self.server.requests().into_iter()
+            .filter(|request| request.query("t") == Some(function))
+            .collect()
+    }
+
+    /// The requests for .torrent files.
+    pub fn downloads(&self) -> Vec<Request> {
+        self.server.requests().into_iter()
+            .filter(|request| request.path.starts_with("/download/"))
+            .collect()
+    }
+}
+
+/// Find a cross-seed of `local` on a new mock indexer and download it. It has
+/// the same files as `local`, but a different info hash.
+pub async fn candidate(local: &TestTorrent) -> Candidate {
+    let indexer = MockIndexer::start("key").await;
+    indexer.add(local.clone().seed(4));
+
+    let client = TorznabClient::new("mock".to_string(), &indexer.url(), "key").await.unwrap();
+    let generic = GenericSearchParametersBuilder::new().query(local.name.clone()).build();
+    let result = client.search(SearchFunction::Search, generic).await.unwrap().results.remove(0);
+
+    Candidate::download(&client, result).await.unwrap()
+}
+
+fn handle(state: &mut MockState, request: &Request) -> Response {
+    if !state.failures.is_empty() {
+        let response = Response::status(state.failures.remove(0));
+        return match state.retry_after {
+            Some(seconds) => response.header("Retry-After", &seconds.to_string()),
+            None => response,
+        };
+    }
+
+    if let Some(id) = request.path.strip_prefix("/download/") {
+        let item = id.trim_end_matches(".torrent").parse::<usize>().ok()
+            .and_then(|id| state.items.get(id));
+
+        return match item {
+            Some(item) => Response::ok("application/x-bittorrent", item.torrent.bytes()),
+            None => Response::status(404),
+        };
+    }
+
+    if request.path != "/api" {
+        return Response::status(404);
+    }
+
+    if request.query("apikey") != Some(state.api_key.as_str()) {
+        return error(100, "Incorrect user credentials");
+    }
+
+    if let Some((code, description)) = &state.error {
+        return error(*code, description);
+    }
+
+    match request.query("t") {
+        Some("caps") => Response::xml(state.caps.clone()),
+        Some("search" | "tvsearch" | "movie" | "music" | "book") => search(state, request),
+        _ => error(202, "No such function"),
+    }
+}
+
+fn error(code: u32, description: &str) -> Response {
+    Response::xml(format!(r#"<?xml version="1.0" encoding="UTF-8"?><error code="{}" description="{}" />"#, code, description))
+}
+
+/// Words of a title or query, ignoring case and separators.
+fn words(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| word.to_lowercase())
+        .collect()
+}
+
+fn search(state: &MockState, request: &Request) -> Response {
+    let query = words(request.query("q").unwrap_or_default());
+
+    let matching: Vec<(usize, &MockItem)> = state.items.iter().enumerate()
+        .filter(|(_, item)| {
+            let title = words(&item.torrent.name);
+            query.iter().all(|word| title.contains(word))
+        })
+        .collect();
+
+    let offset: usize = request.query("offset").and_then(|o| o.parse().ok()).unwrap_or(0);
+    let limit: usize = request.query("limit").and_then(|l| l.parse().ok()).unwrap_or(50);
+
+    let base = format!("http://{}", request.header("host").unwrap_or("localhost"));
+    let items: String = matching.iter()
+        .skip(offset)
+        .take(limit)
+        .map(|(id, item)| rss_item(&base, *id, item))
+        .collect();
+
+    Response::xml(format!(r#"<?xml version="1.0" encoding="UTF-8"?>
+<rss version="2.0" xmlns:atom="http://www.w3.org/2005/Atom" xmlns:torznab="http://torznab.com/schemas/2015/feed" xmlns:newznab="http://www.newznab.com/DTD/2010/feeds/attributes/">
+<channel>
+<title>Mock</title>
+<link>{base}</link>
+<description>Mock indexer</description>
+<newznab:response offset="{offset}" total="{total}" />
+{items}</channel>
+</rss>"#, base = base, offset = offset, total = matching.len(), items = items))
+}
+
+fn rss_item(base: &str, id: usize, item: &MockItem) -> String {
+    let size = item.size.unwrap_or_else(|| item.torrent.total_size());
+    let link = format!("{}/download/{}.torrent", base, id);
+    let tags: String = item.tags.iter()
+        .map(|tag| format!(r#"<torznab:attr name="tag" value="{}" />"#, tag))
+        .collect();
+
+    format!(r#"<item>
+<title>{title}</title>
+<guid>{base}/details/{id}</guid>
+<link>{link}</link>
+<comments>{base}/details/{id}</comments>
+<pubDate>Sat, 01 Oct 2022 12:00:00 +0000</pubDate>
+<size>{size}</size>
+<category>2000</category>
+<enclosure url="{link}" length="{size}" type="application/x-bittorrent" />
+<torznab:attr name="category" value="2000" />
+<torznab:attr name="seeders" value="10" />
+<torznab:attr name="peers" value="12" />
+<torznab:attr name="infohash" value="{info_hash}" />
+<torznab:attr name="downloadvolumefactor" value="0" />
+<torznab:attr name="uploadvolumefactor" value="1" />
+{tags}
+</item>
+"#, title = item.torrent.name, base = base, id = id, link = link, size = size, info_hash = item.torrent.info_hash(), tags = tags)
+}
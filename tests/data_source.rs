@@ -0,0 +1,151 @@
+mod common;
+
+use std::fs::File;
+use std::path::Path;
+
+use common::{MockIndexer, Response, StandIn, TestTorrent};
+
+use cross_seed::config::Config;
+use cross_seed::matching::{FileList, MatchDecision};
+use cross_seed::pipeline;
+use cross_seed::source::{DataDirs, TorrentSource};
+use figment::Figment;
+use figment::providers::{Format, Toml};
+
+const MOVIE: &str = "Some.Movie.2019.1080p.BluRay.x264-GRP";
+const MOVIE_FILES: [(&str, u64); 3] = [
+    ("Some.Movie.2019.1080p.BluRay.x264-GRP.mkv", 80_000_000),
+    ("Some.Movie.2019.1080p.BluRay.x264-GRP.nfo", 4_000),
+    ("Sample/some.movie.2019.sample.mkv", 2_000_000),
+];
+const EPISODE: &str = "Some.Show.S01E01.1080p.WEB.h264-GRP.mkv";
+
+/// Write sparse files with the sizes of the torrent into `dir`.
+fn write_data(dir: &Path, torrent: &TestTorrent) {
+    if torrent.files.is_empty() {
+        File::create(dir.join(&torrent.name)).unwrap().set_len(torrent.length).unwrap();
+        return;
+    }
+
+    for (path, length) in torrent.files.iter() {
+        let path = dir.join(&torrent.name).join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        File::create(path).unwrap().set_len(*length).unwrap();
+    }
+}
+
+#[tokio::test]
+async fn each_top_level_entry_is_an_item() {
+    let dir = tempfile::tempdir().unwrap();
+    let movie = TestTorrent::multi(MOVIE, &MOVIE_FILES);
+    let episode = TestTorrent::single(EPISODE, 1_500_000);
+    write_data(dir.path(), &movie);
+    write_data(dir.path(), &episode);
+
+    // Neither is searched.
+    std::fs::write(dir.path().join(".DS_Store"), b"").unwrap();
+    std::fs::create_dir(dir.path().join("empty")).unwrap();
+
+    let items = DataDirs::new(vec![dir.path().to_path_buf()]).items().await.unwrap();
+    assert_eq!(items.len(), 2);
+
+    assert_eq!(items[0].name, MOVIE);
+    assert_eq!(items[0].files, FileList::from(&movie.torrent()));
+    assert_eq!(items[0].info_hash, None);
+    assert_eq!(items[0].save_path.as_deref(), Some(dir.path().to_str().unwrap()));
+
+    assert_eq!(items[1].name, EPISODE);
+    assert_eq!(items[1].files, FileList::from(&episode.torrent()));
+    assert_eq!(items[1].state_key(), dir.path().join(EPISODE).display().to_string());
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn links_are_followed_unless_they_loop() {
+    use std::os::unix::fs::symlink;
+
+    let dir = tempfile::tempdir().unwrap();
+    let outside = tempfile::tempdir().unwrap();
+    let movie = TestTorrent::multi(MOVIE, &MOVIE_FILES);
+    write_data(outside.path(), &movie);
+
+    // A folder of links to the data, with a link back to itself.
+    let linked = dir.path().join(MOVIE);
+    std::fs::create_dir(&linked).unwrap();
+    for (path, _) in MOVIE_FILES.iter().filter(|(path, _)| !path.contains('/')) {
+        symlink(outside.path().join(MOVIE).join(path), linked.join(path)).unwrap();
+    }
+    symlink(outside.path().join(MOVIE).join("Sample"), linked.join("Sample")).unwrap();
+    symlink(&linked, linked.join("Sample").join("loop")).unwrap();
+    symlink("..", linked.join("parent")).unwrap();
+
+    let items = DataDirs::new(vec![dir.path().to_path_buf()]).items().await.unwrap();
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0].files, FileList::from(&movie.torrent()));
+}
+
+#[tokio::test]
+async fn the_pipeline_cross_seeds_data_into_a_client() {
+    let dir = tempfile::tempdir().unwrap();
+    let data = dir.path().join("data");
+    std::fs::create_dir(&data).unwrap();
+
+    let movie = TestTorrent::multi(MOVIE, &MOVIE_FILES);
+    write_data(&data, &movie);
+
+    let indexer = MockIndexer::start("key").await;
+    indexer.add(movie.clone().seed(5));
+    // Another encode with the same name.
+    indexer.add(TestTorrent::multi(MOVIE, &[("Some.Movie.2019.1080p.BluRay.x264-GRP.mkv", 60_000_000)]).seed(6));
+
+    // qBittorrent without authentication, which doesn't have the cross-seed yet.
+    let qbit = StandIn::start(|request| match request.path.as_str() {
+        "/api/v2/torrents/info" => Response::json("[]"),
+        "/api/v2/torrents/add" => Response::ok("text/plain", "Ok."),
+        _ => Response::status(404),
+    }).await;
+
+    let toml = format!(r#"
+        data_paths = ["{data}"]
+        state_path = "{dir}/state.db"
+
+        [indexers.mock]
+        url = "{indexer}"
+        api_key = "key"
+
+        [clients.qbit]
+        type = "qbittorrent"
+        url = "{qbit}"
+        "#,
+        data = data.display(), dir = dir.path().display(), indexer = indexer.url(), qbit = qbit.url);
+    let config = Config::from_figment(Figment::from(Toml::string(&toml))).unwrap();
+
+    let summary = pipeline::run(&config).await.unwrap();
+
+    assert_eq!(summary.matches.len(), 1);
+    assert_eq!(summary.matches[0].torrent, MOVIE);
+    assert_eq!(summary.matches[0].result.decision, MatchDecision::Match);
+    assert_eq!(summary.matches[0].injected, ["qbit"]);
+
+    // The torrent points at the existing data.
+    let adds: Vec<_> = qbit.requests().into_iter()
+        .filter(|request| request.path == "/api/v2/torrents/add")
+        .collect();
+    assert_eq!(adds.len(), 1);
+    assert!(adds[0].body_str().contains(&format!("name=\"savepath\"\r\n\r\n{}\r\n", data.display())));
+
+    // The data is remembered like a torrent, and not searched again right away.
+    let searches = indexer.requests("movie").len() + indexer.requests("search").len();
+    pipeline::run(&config).await.unwrap();
+    assert_eq!(indexer.requests("movie").len() + indexer.requests("search").len(), searches);
+}
+
+#[tokio::test]
+async fn a_run_needs_a_source() {
+    let dir = tempfile::tempdir().unwrap();
+    let toml = format!(r#"state_path = "{}/state.db""#, dir.path().display());
+    let config = Config::from_figment(Figment::from(Toml::string(&toml))).unwrap();
+
+    let error = pipeline::run(&config).await.unwrap_err();
+    assert!(matches!(error, pipeline::RunError::NoSource), "{}", error);
+}
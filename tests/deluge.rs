@@ -0,0 +1,195 @@
+mod common;
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use common::{candidate, Request, Response, StandIn, TestTorrent};
+
+use cross_seed::clients::{self, ClientConfig, ClientKind, InjectOutcome, TorrentClientError};
+use serde_json::{json, Value};
+
+const NAME: &str = "Some.Show.S01.1080p.WEB.h264-GRP";
+const FILES: [(&str, u64); 2] = [("Some.Show.S01E01.1080p.WEB.h264-GRP.mkv", 2_000_000_000), ("Some.Show.S01E02.1080p.WEB.h264-GRP.mkv", 2_100_000_000)];
+
+#[derive(Default)]
+struct DelugeState {
+    /// Save paths by info hash.
+    torrents: HashMap<String, String>,
+    session: Option<String>,
+    logins: usize,
+    connected: bool,
+    /// Whether the label plugin is disabled.
+    no_labels: bool,
+    /// The params of the RPC calls, by method.
+    calls: Vec<(String, Value)>,
+}
+
+struct FakeDeluge {
+    server: StandIn,
+    state: Arc<Mutex<DelugeState>>,
+}
+
+impl FakeDeluge {
+    async fn start(torrents: &[(&str, &str)]) -> Self {
+        let state = Arc::new(Mutex::new(DelugeState {
+            torrents: torrents.iter().map(|(hash, path)| (hash.to_string(), path.to_string())).collect(),
+            ..DelugeState::default()
+        }));
+
+        let server = {
+            let state = Arc::clone(&state);
+            StandIn::start(move |request| handle(&mut state.lock().unwrap(), request)).await
+        };
+
+        FakeDeluge { server, state }
+    }
+
+    fn config(&self) -> ClientConfig {
+        ClientConfig {
+            name: "deluge".to_string(),
+            kind: ClientKind::Deluge,
+            url: self.server.url.clone(),
+            username: None,
+            password: Some("secret".to_string()),
+            category: Some("Cross-Seed".to_string()),
+            tags: Vec::new(),
+            paused: true,
+            skip_checking: true,
+            verify: false,
+        }
+    }
+
+    fn calls(&self, method: &str) -> Vec<Value> {
+        self.state.lock().unwrap().calls.iter()
+            .filter(|(m, _)| m == method)
+            .map(|(_, params)| params.clone())
+            .collect()
+    }
+
+    fn disable_labels(&self) {
+        self.state.lock().unwrap().no_labels = true;
+    }
+
+    /// Forget the session, like when the web interface restarts.
+    fn expire_session(&self) {
+        self.state.lock().unwrap().session = None;
+    }
+}
+
+fn handle(state: &mut DelugeState, request: &Request) -> Response {
+    if request.path != "/json" {
+        return Response::status(404);
+    }
+
+    let body: Value = serde_json::from_slice(&request.body).unwrap();
+    let method = body["method"].as_str().unwrap().to_string();
+    let params = body["params"].clone();
+    state.calls.push((method.clone(), params.clone()));
+
+    if method == "auth.login" {
+        if params[0] != "secret" {
+            return Response::json(json!({ "result": false, "error": null, "id": 1 }).to_string());
+        }
+
+        state.logins += 1;
+        let session = format!("session{}", state.logins);
+        state.session = Some(session.clone());
+        return Response::json(json!({ "result": true, "error": null, "id": 1 }).to_string())
+            .header("Set-Cookie", &format!("_session_id={}; Path=/json", session));
+    }
+
+    let cookie = state.session.as_ref().map(|session| format!("_session_id={}", session));
+    if cookie.is_none() || request.header("cookie") != cookie.as_deref() {
+        let error = json!({ "message": "Not authenticated", "code": 1 });
+        return Response::json(json!({ "result": null, "error": error, "id": 1 }).to_string());
+    }
+
+    let result = match method.as_str() {
+        "web.connected" => json!(state.connected),
+        "web.get_hosts" => json!([["host1", "127.0.0.1", 58846, "localclient"]]),
+        "web.connect" => {
+            state.connected = params[0] == "host1";
+            json!([])
+        },
+        "core.get_torrent_status" => match state.torrents.get(params[0].as_str().unwrap()) {
+            Some(path) => json!({ "save_path": path }),
+            None => json!({}),
+        },
+        "core.add_torrent_file" => json!("added"),
+        "label.add" | "label.set_torrent" if state.no_labels => {
+            let error = json!({ "message": "Unknown method", "code": 2 });
+            return Response::json(json!({ "result": null, "error": error, "id": 1 }).to_string());
+        },
+        "label.add" => {
+            let error = json!({ "message": "Label already exists", "code": 4 });
+            return Response::json(json!({ "result": null, "error": error, "id": 1 }).to_string());
+        },
+        "label.set_torrent" => Value::Null,
+        _ => {
+            let error = json!({ "message": "Unknown method", "code": 2 });
+            return Response::json(json!({ "result": null, "error": error, "id": 1 }).to_string());
+        },
+    };
+
+    Response::json(json!({ "result": result, "error": null, "id": 1 }).to_string())
+}
+
+#[tokio::test]
+async fn adds_the_cross_seed_to_the_save_path_of_the_original() {
+    let local = TestTorrent::multi(NAME, &FILES);
+    let deluge = FakeDeluge::start(&[(&local.info_hash(), "/data/tv")]).await;
+    let client = deluge.config().create_client();
+    let candidate = candidate(&local).await;
+
+    let outcome = clients::inject(client.as_ref(), &local.info_hash(), &candidate).await.unwrap();
+    assert_eq!(outcome, InjectOutcome::Added("/data/tv".to_string()));
+
+    let adds = deluge.calls("core.add_torrent_file");
+    assert_eq!(adds.len(), 1);
+    assert_eq!(base64::decode(adds[0][1].as_str().unwrap()).unwrap(), candidate.bytes.as_ref());
+    assert_eq!(adds[0][2], json!({ "download_location": "/data/tv", "add_paused": true, "seed_mode": true }));
+
+    // The label already exists, which doesn't stop it from being set.
+    assert_eq!(deluge.calls("label.set_torrent"), [json!(["added", "cross-seed"])]);
+}
+
+#[tokio::test]
+async fn adds_the_cross_seed_without_the_label_plugin() {
+    let local = TestTorrent::multi(NAME, &FILES);
+    let deluge = FakeDeluge::start(&[(&local.info_hash(), "/data/tv")]).await;
+    deluge.disable_labels();
+    let client = deluge.config().create_client();
+    let candidate = candidate(&local).await;
+
+    let outcome = clients::inject(client.as_ref(), &local.info_hash(), &candidate).await.unwrap();
+    assert_eq!(outcome, InjectOutcome::Added("/data/tv".to_string()));
+    assert_eq!(deluge.calls("core.add_torrent_file").len(), 1);
+    assert_eq!(deluge.calls("label.set_torrent").len(), 1);
+}
+
+#[tokio::test]
+async fn connects_to_the_daemon_and_logs_in_again() {
+    let deluge = FakeDeluge::start(&[("abc", "/data")]).await;
+    let client = deluge.config().create_client();
+
+    assert_eq!(client.save_path("abc").await.unwrap().as_deref(), Some("/data"));
+    assert_eq!(client.save_path("def").await.unwrap(), None);
+    assert_eq!(deluge.calls("web.connect"), [json!(["host1"])]);
+
+    deluge.expire_session();
+    assert_eq!(client.save_path("abc").await.unwrap().as_deref(), Some("/data"));
+
+    assert_eq!(deluge.calls("auth.login").len(), 2);
+    assert_eq!(deluge.calls("web.connect").len(), 1);
+    assert_eq!(deluge.calls("core.get_torrent_status").len(), 4);
+}
+
+#[tokio::test]
+async fn wrong_password_fails() {
+    let deluge = FakeDeluge::start(&[]).await;
+    let mut config = deluge.config();
+    config.password = Some("wrong".to_string());
+
+    let error = config.create_client().save_path("abc").await.unwrap_err();
+    assert!(matches!(error, TorrentClientError::LoginFailed), "{}", error);
+}
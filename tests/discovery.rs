@@ -0,0 +1,119 @@
+mod common;
+
+use common::{Response, StandIn};
+
+use cross_seed::discovery::{Discovery, DiscoveryKind};
+use cross_seed::torznab::RetryPolicy;
+
+const JACKETT_INDEXERS: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<indexers>
+  <indexer id="1337x" configured="true">
+    <title>1337x</title>
+    <description>1337X is a Public torrent site</description>
+    <link>https://1337x.to/</link>
+    <language>en-US</language>
+    <type>public</type>
+    <caps>
+      <server title="Jackett" />
+      <limits default="100" max="100" />
+      <searching>
+        <search available="yes" supportedParams="q" />
+      </searching>
+      <categories>
+        <category id="2000" name="Movies" />
+      </categories>
+    </caps>
+  </indexer>
+  <indexer id="privatehd" configured="true">
+    <title>PrivateHD</title>
+    <type>private</type>
+  </indexer>
+  <indexer id="unused" configured="false">
+    <title>Unused</title>
+  </indexer>
+</indexers>"#;
+
+const PROWLARR_INDEXERS: &str = r#"[
+  {"id": 1, "name": "1337x", "enable": true, "protocol": "torrent", "privacy": "public"},
+  {"id": 2, "name": "NZBgeek", "enable": true, "protocol": "usenet"},
+  {"id": 5, "name": "PrivateHD", "enable": true, "protocol": "torrent"},
+  {"id": 7, "name": "Disabled", "enable": false, "protocol": "torrent"}
+]"#;
+
+fn discovery(kind: DiscoveryKind, url: &str) -> Discovery {
+    Discovery {
+        name: "manager".to_string(),
+        kind,
+        url: url.to_string(),
+        api_key: "secret key".to_string(),
+        include: Vec::new(),
+        exclude: Vec::new(),
+        max_concurrency: Some(3),
+        rate_limit: None,
+        retry: RetryPolicy::default(),
+    }
+}
+
+#[tokio::test]
+async fn jackett_lists_configured_indexers() {
+    let server = StandIn::start(|_| Response::xml(JACKETT_INDEXERS)).await;
+
+    let indexers = discovery(DiscoveryKind::Jackett, &server.url).indexers().await.unwrap();
+
+    let names: Vec<_> = indexers.iter().map(|i| i.name.as_str()).collect();
+    assert_eq!(names, ["1337x", "PrivateHD"]);
+    assert_eq!(indexers[0].url, format!("{}/api/v2.0/indexers/1337x/results/torznab/api", server.url));
+    assert_eq!(indexers[0].api_key, "secret key");
+    assert_eq!(indexers[0].max_concurrency, Some(3));
+
+    let requests = server.requests();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].path, "/api/v2.0/indexers/all/results/torznab/api");
+    assert_eq!(requests[0].query("apikey"), Some("secret key"));
+    assert_eq!(requests[0].query("t"), Some("indexers"));
+}
+
+#[tokio::test]
+async fn prowlarr_lists_enabled_torrent_indexers() {
+    let server = StandIn::start(|_| Response::json(PROWLARR_INDEXERS)).await;
+
+    // A trailing slash in the base url doesn't end up in the paths.
+    let url = format!("{}/", server.url);
+    let indexers = discovery(DiscoveryKind::Prowlarr, &url).indexers().await.unwrap();
+
+    let names: Vec<_> = indexers.iter().map(|i| i.name.as_str()).collect();
+    assert_eq!(names, ["1337x", "PrivateHD"]);
+    assert_eq!(indexers[1].url, format!("{}/5/api", server.url));
+
+    let requests = server.requests();
+    assert_eq!(requests[0].path, "/api/v1/indexer");
+    assert_eq!(requests[0].header("X-Api-Key"), Some("secret key"));
+}
+
+#[tokio::test]
+async fn include_and_exclude_filter_by_name() {
+    let server = StandIn::start(|_| Response::json(PROWLARR_INDEXERS)).await;
+
+    let mut included = discovery(DiscoveryKind::Prowlarr, &server.url);
+    included.include = vec!["privatehd".to_string()];
+    let indexers = included.indexers().await.unwrap();
+    assert_eq!(indexers.len(), 1);
+    assert_eq!(indexers[0].name, "PrivateHD");
+
+    let mut excluded = discovery(DiscoveryKind::Prowlarr, &server.url);
+    excluded.exclude = vec!["1337X".to_string()];
+    let indexers = excluded.indexers().await.unwrap();
+    assert_eq!(indexers.len(), 1);
+    assert_eq!(indexers[0].name, "PrivateHD");
+}
+
+#[tokio::test]
+async fn errors_hide_the_api_key() {
+    let server = StandIn::start(|_| Response::status(401)).await;
+
+    let error = discovery(DiscoveryKind::Jackett, &server.url).indexers().await.unwrap_err();
+
+    let message = error.to_string();
+    assert!(message.contains("401"), "{}", message);
+    assert!(!message.contains("secret"), "{}", message);
+}
@@ -0,0 +1,100 @@
+mod common;
+
+use std::path::Path;
+
+use common::{candidate, TestTorrent};
+
+use cross_seed::output::{OutputTemplate, SaveOutcome, TorrentWriter, MAX_NAME_COLLISIONS};
+
+const NAME: &str = "Some.Movie.2019.1080p.BluRay.x264-GRP";
+
+/// The names of the files in the directory.
+fn file_names(dir: &Path) -> Vec<String> {
+    let mut names: Vec<String> = std::fs::read_dir(dir).unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+        .collect();
+    names.sort();
+
+    names
+}
+
+#[tokio::test]
+async fn saves_the_torrent_once() {
+    let dir = tempfile::tempdir().unwrap();
+    let writer = TorrentWriter::new(dir.path(), OutputTemplate::default());
+    let candidate = candidate(&TestTorrent::single(NAME, 1_000)).await;
+
+    let path = dir.path().join(format!("[mock] {}.torrent", NAME));
+    assert_eq!(writer.save("mock", &candidate).await.unwrap(), SaveOutcome::Saved(path.clone()));
+    assert_eq!(std::fs::read(&path).unwrap(), candidate.bytes.as_ref());
+
+    // The same file isn't saved again under another name.
+    assert_eq!(writer.save("mock", &candidate).await.unwrap(), SaveOutcome::AlreadyExists(path));
+    assert_eq!(file_names(dir.path()), vec![format!("[mock] {}.torrent", NAME)]);
+}
+
+#[tokio::test]
+async fn numbers_different_torrents_with_the_same_name() {
+    let dir = tempfile::tempdir().unwrap();
+    let writer = TorrentWriter::new(dir.path(), OutputTemplate::default());
+    let first = candidate(&TestTorrent::single(NAME, 1_000)).await;
+    let second = candidate(&TestTorrent::single(NAME, 2_000)).await;
+    let third = candidate(&TestTorrent::single(NAME, 3_000)).await;
+
+    for candidate in [&first, &second, &third, &second] {
+        writer.save("mock", candidate).await.unwrap();
+    }
+
+    assert_eq!(file_names(dir.path()), vec![
+        format!("[mock] {} (1).torrent", NAME),
+        format!("[mock] {} (2).torrent", NAME),
+        format!("[mock] {}.torrent", NAME),
+    ]);
+    assert_eq!(std::fs::read(dir.path().join(format!("[mock] {} (2).torrent", NAME))).unwrap(), third.bytes.as_ref());
+}
+
+#[tokio::test]
+async fn numbers_names_that_end_in_torrent_twice() {
+    let dir = tempfile::tempdir().unwrap();
+    let writer = TorrentWriter::new(dir.path(), "{name}.torrent".parse().unwrap());
+    let first = candidate(&TestTorrent::single("Pack.torrent", 1_000)).await;
+    let second = candidate(&TestTorrent::single("Pack.torrent", 2_000)).await;
+
+    for candidate in [&first, &second] {
+        writer.save("mock", candidate).await.unwrap();
+    }
+
+    // Only the extension is replaced by the number.
+    assert_eq!(file_names(dir.path()), vec![
+        "Pack.torrent (1).torrent".to_string(),
+        "Pack.torrent.torrent".to_string(),
+    ]);
+}
+
+#[tokio::test]
+async fn gives_up_when_every_numbered_name_is_taken() {
+    let dir = tempfile::tempdir().unwrap();
+    let writer = TorrentWriter::new(dir.path(), OutputTemplate::default());
+    let candidate = candidate(&TestTorrent::single(NAME, 1_000)).await;
+
+    std::fs::write(dir.path().join(format!("[mock] {}.torrent", NAME)), "other").unwrap();
+    for n in 1..=MAX_NAME_COLLISIONS {
+        std::fs::write(dir.path().join(format!("[mock] {} ({}).torrent", NAME, n)), "other").unwrap();
+    }
+
+    assert!(writer.save("mock", &candidate).await.is_err());
+    assert_eq!(file_names(dir.path()).len(), MAX_NAME_COLLISIONS as usize + 1);
+}
+
+#[tokio::test]
+async fn failed_saves_leave_no_files_behind() {
+    let dir = tempfile::tempdir().unwrap();
+    let writer = TorrentWriter::new(dir.path(), OutputTemplate::default());
+    let candidate = candidate(&TestTorrent::single(NAME, 1_000)).await;
+
+    // A directory can't be read or replaced like a file.
+    std::fs::create_dir(dir.path().join(format!("[mock] {}.torrent", NAME))).unwrap();
+
+    assert!(writer.save("mock", &candidate).await.is_err());
+    assert_eq!(file_names(dir.path()), vec![format!("[mock] {}.torrent", NAME)]);
+}
@@ -0,0 +1,101 @@
+mod common;
+
+use std::path::Path;
+
+use common::{MockIndexer, MockItem, TestTorrent};
+
+use cross_seed::config::Config;
+use cross_seed::matching::MatchDecision;
+use cross_seed::pipeline;
+use figment::Figment;
+use figment::providers::{Format, Toml};
+
+const API_KEY: &str = "mock-key";
+
+const MOVIE: &str = "Some.Movie.2019.1080p.BluRay.x264-GRP";
+const MOVIE_FILES: [(&str, u64); 2] = [("Some.Movie.2019.1080p.BluRay.x264-GRP.mkv", 8_000_000_000), ("Some.Movie.2019.1080p.BluRay.x264-GRP.nfo", 4_000)];
+
+fn config(dir: &Path, indexer: &MockIndexer) -> Config {
+    let toml = format!(r#"
+        torrents_path = "{dir}/torrents"
+        output_path = "{dir}/output"
+        state_path = "{dir}/state.db"
+
+        [indexers.mock]
+        url = "{url}"
+        api_key = "{key}"
+
+        [indexers.mock.retry]
+        base_delay_ms = 1
+        "#,
+        dir = dir.display(), url = indexer.url(), key = API_KEY);
+
+    Config::from_figment(Figment::from(Toml::string(&toml))).unwrap()
+}
+
+fn write_torrent(dir: &Path, torrent: &TestTorrent) {
+    let torrents = dir.join("torrents");
+    std::fs::create_dir_all(&torrents).unwrap();
+    std::fs::write(torrents.join(format!("{}.torrent", torrent.name)), torrent.bytes()).unwrap();
+}
+
+#[tokio::test]
+async fn finds_and_saves_cross_seeds() {
+    let dir = tempfile::tempdir().unwrap();
+    let indexer = MockIndexer::start(API_KEY).await;
+
+    let local = TestTorrent::multi(MOVIE, &MOVIE_FILES);
+    write_torrent(dir.path(), &local);
+
+    // The same torrent, which is where the local one came from.
+    indexer.add(local.clone());
+    // The same files on another tracker.
+    indexer.add(TestTorrent::multi(MOVIE, &MOVIE_FILES).seed(1));
+    // Another encode with the same name.
+    indexer.add_item(MockItem {
+        torrent: TestTorrent::multi(MOVIE, &[("Some.Movie.2019.1080p.BluRay.x264-GRP.mkv", 6_000_000_000)]).seed(2),
+        size: None,
+        tags: Vec::new(),
+    });
+
+    let config = config(dir.path(), &indexer);
+    let summary = pipeline::run(&config).await.unwrap();
+
+    assert_eq!(summary.matches.len(), 1);
+    let found = &summary.matches[0];
+    assert_eq!(found.torrent, MOVIE);
+    assert_eq!(found.indexer, "mock");
+    assert_eq!(found.result.decision, MatchDecision::Match);
+
+    let saved = found.saved.as_ref().unwrap();
+    assert_eq!(saved, &dir.path().join("output").join(format!("[mock] {}.torrent", MOVIE)));
+    assert_eq!(std::fs::read(saved).unwrap(), TestTorrent::multi(MOVIE, &MOVIE_FILES).seed(1).bytes());
+
+    // The movie is searched with its title and year.
+    let searches = indexer.requests("movie");
+    assert_eq!(searches.len(), 1);
+    assert_eq!(searches[0].query("q"), Some("Some Movie"));
+    assert_eq!(searches[0].query("year"), Some("2019"));
+
+    // Only the cross-seed needed to be downloaded.
+    assert_eq!(indexer.downloads().len(), 1);
+
+    // A second run doesn't search the torrent again.
+    let summary = pipeline::run(&config).await.unwrap();
+    assert!(summary.matches.is_empty());
+    assert_eq!(indexer.requests("movie").len(), 1);
+}
+
+#[tokio::test]
+async fn skips_indexers_that_fail() {
+    let dir = tempfile::tempdir().unwrap();
+    let indexer = MockIndexer::start(API_KEY).await;
+    indexer.set_error(100, "Incorrect user credentials");
+    write_torrent(dir.path(), &TestTorrent::multi(MOVIE, &MOVIE_FILES));
+
+    let summary = pipeline::run(&config(dir.path(), &indexer)).await.unwrap();
+
+    assert!(summary.matches.is_empty());
+    assert_eq!(summary.failed_indexers, ["mock"]);
+    assert!(indexer.requests("movie").is_empty());
+}
@@ -0,0 +1,408 @@
+mod common;
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use common::{candidate, MockIndexer, Request, Response, StandIn, TestTorrent};
+
+use cross_seed::clients::{self, ClientConfig, ClientKind, InjectOutcome, TorrentClientError};
+use cross_seed::config::Config;
+use cross_seed::matching::FileList;
+use cross_seed::pipeline;
+use figment::Figment;
+use figment::providers::{Format, Toml};
+use serde_json::{json, Value};
+
+const NAME: &str = "Some.Movie.2019.1080p.BluRay.x264-GRP";
+const FILES: [(&str, u64); 2] = [("Some.Movie.2019.1080p.BluRay.x264-GRP.mkv", 8_000_000_000), ("Some.Movie.2019.1080p.BluRay.x264-GRP.nfo", 4_000)];
+
+/// The state of the qBittorrent stand-in.
+#[derive(Default)]
+struct QBittorrentState {
+    /// Save paths by info hash.
+    torrents: HashMap<String, String>,
+    /// The torrents listed without a hash, with their files by hash.
+    listed: Vec<(Value, Value)>,
+    sessions: Vec<String>,
+    logins: usize,
+}
+
+struct FakeQBittorrent {
+    server: StandIn,
+    state: Arc<Mutex<QBittorrentState>>,
+}
+
+impl FakeQBittorrent {
+    async fn start(torrents: &[(&str, &str)]) -> Self {
+        let state = Arc::new(Mutex::new(QBittorrentState {
+            torrents: torrents.iter().map(|(hash, path)| (hash.to_string(), path.to_string())).collect(),
+            ..QBittorrentState::default()
+        }));
+
+        let server = {
+            let state = Arc::clone(&state);
+            StandIn::start(move |request| handle(&mut state.lock().unwrap(), request)).await
+        };
+
+        FakeQBittorrent { server, state }
+    }
+
+    fn config(&self) -> ClientConfig {
+        ClientConfig {
+            name: "qbit".to_string(),
+            kind: ClientKind::QBittorrent,
+            url: self.server.url.clone(),
+            username: Some("admin".to_string()),
+            password: Some("adminadmin".to_string()),
+            category: Some("cross-seed".to_string()),
+            tags: vec!["cross-seed".to_string(), "auto".to_string()],
+            paused: true,
+            skip_checking: false,
+            verify: false,
+        }
+    }
+
+    /// List a torrent with the files of `torrent` in `/data/movies`.
+    fn list(&self, torrent: &TestTorrent, state: &str, progress: f64) {
+        let hash = torrent.info_hash();
+        let info = json!({
+            "hash": hash.to_uppercase(),
+            "name": torrent.name,
+            "save_path": "/data/movies",
+            "state": state,
+            "progress": progress,
+            "category": "movies",
+            "tags": "hd, radarr",
+        });
+        let files: Vec<Value> = torrent.files.iter()
+            .map(|(path, size)| json!({ "name": format!("{}/{}", torrent.name, path), "size": size }))
+            .collect();
+
+        let mut state = self.state.lock().unwrap();
+        state.torrents.insert(hash, "/data/movies".to_string());
+        state.listed.push((info, Value::Array(files)));
+    }
+
+    fn adds(&self) -> Vec<Request> {
+        self.server.requests().into_iter()
+            .filter(|request| request.path == "/api/v2/torrents/add")
+            .collect()
+    }
+
+    /// Log out every session, like when qBittorrent restarts.
+    fn expire_sessions(&self) {
+        self.state.lock().unwrap().sessions.clear();
+    }
+}
+
+fn handle(state: &mut QBittorrentState, request: &Request) -> Response {
+    if request.path == "/api/v2/auth/login" {
+        state.logins += 1;
+        if request.body_str() != "username=admin&password=adminadmin" {
+            return Response::ok("text/plain", "Fails.");
+        }
+
+        let session = format!("session{}", state.logins);
+        state.sessions.push(session.clone());
+        return Response::ok("text/plain", "Ok.").header("Set-Cookie", &format!("SID={}; HttpOnly; path=/", session));
+    }
+
+    let logged_in = request.header("cookie")
+        .and_then(|cookie| cookie.strip_prefix("SID="))
+        .is_some_and(|session| state.sessions.iter().any(|s| s == session));
+    if !logged_in {
+        return Response::status(403);
+    }
+
+    match request.path.as_str() {
+        "/api/v2/torrents/info" if request.query("hashes").is_none() => {
+            let infos: Vec<Value> = state.listed.iter().map(|(info, _)| info.clone()).collect();
+            Response::json(Value::Array(infos).to_string())
+        },
+        "/api/v2/torrents/files" => {
+            let hash = request.query("hash").unwrap_or_default();
+            match state.listed.iter().find(|(info, _)| info["hash"] == hash) {
+                Some((_, files)) => Response::json(files.to_string()),
+                None => Response::status(404),
+            }
+        },
+        "/api/v2/torrents/info" => {
+            let hash = request.query("hashes").unwrap_or_default();
+            let body = match state.torrents.get(hash) {
+                Some(path) => format!(r#"[{{"hash": "{}", "name": "x", "save_path": "{}", "state": "uploading", "progress": 1}}]"#, hash, path),
+                None => "[]".to_string(),
+            };
+            Response::json(body)
+        },
+        "/api/v2/torrents/add" => Response::ok("text/plain", "Ok."),
+        _ => Response::status(404),
+    }
+}
+
+/// The value of a text field of a multipart body.
+fn field(request: &Request, name: &str) -> Option<String> {
+    let body = request.body_str();
+    let needle = format!("name=\"{}\"\r\n\r\n", name);
+    let start = body.find(&needle)? + needle.len();
+    let end = body[start..].find("\r\n")?;
+
+    Some(body[start..start + end].to_string())
+}
+
+#[tokio::test]
+async fn adds_the_cross_seed_next_to_the_original() {
+    let local = TestTorrent::multi(NAME, &FILES);
+    let qbit = FakeQBittorrent::start(&[(&local.info_hash(), "/data/movies")]).await;
+    let client = qbit.config().create_client();
+    let candidate = candidate(&local).await;
+
+    let outcome = clients::inject(client.as_ref(), &local.info_hash(), &candidate).await.unwrap();
+    assert_eq!(outcome, InjectOutcome::Added("/data/movies".to_string()));
+
+    let adds = qbit.adds();
+    assert_eq!(adds.len(), 1);
+    assert_eq!(field(&adds[0], "savepath").as_deref(), Some("/data/movies"));
+    assert_eq!(field(&adds[0], "autoTMM").as_deref(), Some("false"));
+    assert_eq!(field(&adds[0], "paused").as_deref(), Some("true"));
+    assert_eq!(field(&adds[0], "skip_checking").as_deref(), Some("false"));
+    assert_eq!(field(&adds[0], "category").as_deref(), Some("cross-seed"));
+    assert_eq!(field(&adds[0], "tags").as_deref(), Some("cross-seed,auto"));
+    assert!(adds[0].body.windows(candidate.bytes.len()).any(|w| w == candidate.bytes.as_ref()));
+
+    // The session is reused.
+    assert_eq!(qbit.state.lock().unwrap().logins, 1);
+}
+
+#[tokio::test]
+async fn skips_clients_without_the_original_or_with_the_cross_seed() {
+    let local = TestTorrent::multi(NAME, &FILES);
+    let candidate = candidate(&local).await;
+
+    let qbit = FakeQBittorrent::start(&[]).await;
+    let client = qbit.config().create_client();
+    let outcome = clients::inject(client.as_ref(), &local.info_hash(), &candidate).await.unwrap();
+    assert_eq!(outcome, InjectOutcome::OriginalNotFound);
+
+    let cross_seed_hash = candidate.torrent.info_hash();
+    let qbit = FakeQBittorrent::start(&[(&local.info_hash(), "/data"), (&cross_seed_hash, "/data")]).await;
+    let client = qbit.config().create_client();
+    let outcome = clients::inject(client.as_ref(), &local.info_hash(), &candidate).await.unwrap();
+    assert_eq!(outcome, InjectOutcome::AlreadyExists);
+
+    assert!(qbit.adds().is_empty());
+}
+
+#[tokio::test]
+async fn logs_in_again_when_the_session_expires() {
+    let qbit = FakeQBittorrent::start(&[("abc", "/data")]).await;
+    let client = qbit.config().create_client();
+
+    assert_eq!(client.save_path("abc").await.unwrap().as_deref(), Some("/data"));
+    qbit.expire_sessions();
+    assert_eq!(client.save_path("abc").await.unwrap().as_deref(), Some("/data"));
+
+    assert_eq!(qbit.state.lock().unwrap().logins, 2);
+}
+
+#[tokio::test]
+async fn wrong_credentials_fail_the_login() {
+    let qbit = FakeQBittorrent::start(&[]).await;
+    let mut config = qbit.config();
+    config.password = Some("wrong".to_string());
+
+    let error = config.create_client().save_path("abc").await.unwrap_err();
+    assert!(matches!(error, TorrentClientError::LoginFailed), "{}", error);
+}
+
+#[tokio::test]
+async fn the_pipeline_adds_matches_to_the_clients() {
+    let dir = tempfile::tempdir().unwrap();
+    let local = TestTorrent::multi(NAME, &FILES);
+    let torrents = dir.path().join("torrents");
+    std::fs::create_dir_all(&torrents).unwrap();
+    std::fs::write(torrents.join("local.torrent"), local.bytes()).unwrap();
+
+    let indexer = MockIndexer::start("key").await;
+    indexer.add(local.clone().seed(3));
+    let qbit = FakeQBittorrent::start(&[(&local.info_hash(), "/data/movies")]).await;
+
+    let toml = format!(r#"
+        torrents_path = "{dir}/torrents"
+        state_path = "{dir}/state.db"
+
+        [indexers.mock]
+        url = "{indexer}"
+        api_key = "key"
+
+        [clients.qbit]
+        type = "qbittorrent"
+        url = "{qbit}"
+        username = "admin"
+        password = "adminadmin"
+        "#,
+        dir = dir.path().display(), indexer = indexer.url(), qbit = qbit.server.url);
+    let config = Config::from_figment(Figment::from(Toml::string(&toml))).unwrap();
+
+    let summary = pipeline::run(&config).await.unwrap();
+
+    assert_eq!(summary.matches.len(), 1);
+    assert_eq!(summary.matches[0].injected, ["qbit"]);
+
+    let adds = qbit.adds();
+    assert_eq!(adds.len(), 1);
+    assert_eq!(field(&adds[0], "savepath").as_deref(), Some("/data/movies"));
+    // Paused unless configured otherwise.
+    assert_eq!(field(&adds[0], "paused").as_deref(), Some("true"));
+    assert_eq!(field(&adds[0], "category"), None);
+}
+
+#[tokio::test]
+async fn lists_stopped_torrents_but_not_checking_ones() {
+    let qbit = FakeQBittorrent::start(&[]).await;
+    let cases = [
+        ("pausedUP", 1.0, true),
+        ("stoppedUP", 1.0, true),
+        ("queuedUP", 1.0, true),
+        ("checkingUP", 1.0, false),
+        ("missingFiles", 1.0, false),
+        ("stoppedDL", 0.5, false),
+    ];
+
+    let torrents: Vec<_> = cases.iter()
+        .map(|(state, progress, _)| {
+            let torrent = TestTorrent::single(&format!("{}.Movie.2019.1080p.BluRay.x264-GRP", state), 1_000);
+            qbit.list(&torrent, state, *progress);
+            torrent
+        })
+        .collect();
+
+    let listed: Vec<String> = qbit.config().create_client().torrents().await.unwrap().into_iter()
+        .map(|torrent| torrent.info_hash)
+        .collect();
+
+    for ((state, _, expected), torrent) in cases.iter().zip(&torrents) {
+        assert_eq!(listed.contains(&torrent.info_hash()), *expected, "{}", state);
+    }
+}
+
+#[tokio::test]
+async fn lists_the_torrents() {
+    let local = TestTorrent::multi(NAME, &FILES);
+    let qbit = FakeQBittorrent::start(&[]).await;
+    qbit.list(&local, "stalledUP", 1.0);
+
+    let torrents = qbit.config().create_client().torrents().await.unwrap();
+    assert_eq!(torrents.len(), 1);
+
+    let torrent = &torrents[0];
+    assert_eq!(torrent.info_hash, local.info_hash());
+    assert_eq!(torrent.name, NAME);
+    assert_eq!(torrent.save_path, "/data/movies");
+    assert_eq!(torrent.category.as_deref(), Some("movies"));
+    assert_eq!(torrent.tags, ["hd", "radarr"]);
+    assert!(torrent.is_complete());
+    assert_eq!(torrent.files, FileList::from(&local.torrent()));
+}
+
+#[tokio::test]
+async fn the_pipeline_searches_the_complete_torrents_of_a_client() {
+    let dir = tempfile::tempdir().unwrap();
+    let seeding = TestTorrent::multi(NAME, &FILES);
+    let downloading = TestTorrent::multi("Other.Movie.2020.1080p.BluRay.x264-GRP", &[("Other.Movie.2020.1080p.BluRay.x264-GRP.mkv", 7_000_000_000)]);
+    let missing = TestTorrent::multi("Third.Movie.2021.1080p.BluRay.x264-GRP", &[("Third.Movie.2021.1080p.BluRay.x264-GRP.mkv", 6_000_000_000)]);
+
+    let indexer = MockIndexer::start("key").await;
+    indexer.add(seeding.clone().seed(3));
+    indexer.add(downloading.clone().seed(3));
+    indexer.add(missing.clone().seed(3));
+
+    let qbit = FakeQBittorrent::start(&[]).await;
+    qbit.list(&seeding, "uploading", 1.0);
+    qbit.list(&downloading, "downloading", 0.5);
+    qbit.list(&missing, "missingFiles", 1.0);
+
+    let toml = format!(r#"
+        source_client = "qbit"
+        state_path = "{dir}/state.db"
+
+        [indexers.mock]
+        url = "{indexer}"
+        api_key = "key"
+
+        [clients.qbit]
+        type = "qbittorrent"
+        url = "{qbit}"
+        username = "admin"
+        password = "adminadmin"
+        "#,
+        dir = dir.path().display(), indexer = indexer.url(), qbit = qbit.server.url);
+    let config = Config::from_figment(Figment::from(Toml::string(&toml))).unwrap();
+
+    let summary = pipeline::run(&config).await.unwrap();
+
+    // Only the complete torrent is searched, or has its files listed.
+    assert_eq!(indexer.requests("search").len() + indexer.requests("movie").len(), 1);
+    let file_lists: Vec<_> = qbit.server.requests().into_iter()
+        .filter(|request| request.path == "/api/v2/torrents/files")
+        .collect();
+    assert_eq!(file_lists.len(), 1);
+    assert_eq!(file_lists[0].query("hash").map(str::to_lowercase), Some(seeding.info_hash()));
+    assert_eq!(summary.matches.len(), 1);
+    assert_eq!(summary.matches[0].torrent, NAME);
+    assert_eq!(summary.matches[0].injected, ["qbit"]);
+    assert_eq!(field(&qbit.adds()[0], "savepath").as_deref(), Some("/data/movies"));
+}
+
+#[tokio::test]
+async fn the_pipeline_searches_torrents_in_more_than_one_source_once() {
+    let dir = tempfile::tempdir().unwrap();
+    let local = TestTorrent::multi(NAME, &FILES);
+
+    let indexer = MockIndexer::start("key").await;
+    indexer.add(local.clone().seed(3));
+
+    let qbit = FakeQBittorrent::start(&[]).await;
+    qbit.list(&local, "uploading", 1.0);
+
+    // The client keeps a copy of the .torrent files it has.
+    let torrents = dir.path().join("torrents");
+    std::fs::create_dir_all(&torrents).unwrap();
+    std::fs::write(torrents.join(format!("{}.torrent", local.info_hash())), local.bytes()).unwrap();
+
+    let toml = format!(r#"
+        source_client = "qbit"
+        torrents_path = "{dir}/torrents"
+        state_path = "{dir}/state.db"
+
+        [indexers.mock]
+        url = "{indexer}"
+        api_key = "key"
+
+        [clients.qbit]
+        type = "qbittorrent"
+        url = "{qbit}"
+        username = "admin"
+        password = "adminadmin"
+        "#,
+        dir = dir.path().display(), indexer = indexer.url(), qbit = qbit.server.url);
+    let config = Config::from_figment(Figment::from(Toml::string(&toml))).unwrap();
+
+    let summary = pipeline::run(&config).await.unwrap();
+
+    assert_eq!(indexer.requests("search").len() + indexer.requests("movie").len(), 1);
+    assert_eq!(summary.matches.len(), 1);
+}
+
+#[tokio::test]
+async fn an_unknown_source_client_fails_the_run() {
+    let dir = tempfile::tempdir().unwrap();
+    let toml = format!(r#"
+        source_client = "missing"
+        state_path = "{dir}/state.db"
+        "#,
+        dir = dir.path().display());
+    let config = Config::from_figment(Figment::from(Toml::string(&toml))).unwrap();
+
+    let error = pipeline::run(&config).await.unwrap_err();
+    assert!(matches!(error, pipeline::RunError::UnknownClient(ref name) if name == "missing"), "{}", error);
+}
@@ -0,0 +1,220 @@
+mod common;
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use common::{candidate, Response, StandIn, TestTorrent};
+
+use cross_seed::clients::{self, ClientConfig, ClientKind, InjectOutcome};
+use regex::Regex;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+const NAME: &str = "Some.Show.S01.1080p.WEB.h264-GRP";
+const FILES: [(&str, u64); 2] = [("Some.Show.S01E01.1080p.WEB.h264-GRP.mkv", 2_000_000_000), ("Some.Show.S01E02.1080p.WEB.h264-GRP.mkv", 2_100_000_000)];
+
+/// A parameter of a received call.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Param {
+    String(String),
+    Base64(Vec<u8>),
+}
+
+#[derive(Default)]
+struct RTorrentState {
+    /// `d.directory` and whether the torrent has multiple files, by uppercase info hash.
+    torrents: HashMap<String, (String, bool)>,
+    calls: Vec<(String, Vec<Param>)>,
+}
+
+type SharedState = Arc<Mutex<RTorrentState>>;
+
+fn value(value: &str) -> String {
+    format!("<?xml version=\"1.0\"?><methodResponse><params><param><value>{}</value></param></params></methodResponse>", value)
+}
+
+fn fault(code: i32, message: &str) -> String {
+    format!("<?xml version=\"1.0\"?><methodResponse><fault><value><struct>\
+        <member><name>faultCode</name><value><i4>{}</i4></value></member>\
+        <member><name>faultString</name><value><string>{}</string></value></member>\
+        </struct></value></fault></methodResponse>", code, message)
+}
+
+fn handle(state: &SharedState, body: &[u8]) -> String {
+    let body = String::from_utf8(body.to_vec()).unwrap();
+    let method = Regex::new("<methodName>(.*?)</methodName>").unwrap().captures(&body).unwrap()[1].to_string();
+    let params: Vec<Param> = Regex::new("<value><(string|base64)>(.*?)</(?:string|base64)></value>").unwrap()
+        .captures_iter(&body)
+        .map(|captures| match &captures[1] {
+            "base64" => Param::Base64(base64::decode(&captures[2]).unwrap()),
+            _ => Param::String(captures[2].replace("&quot;", "\"").replace("&amp;", "&")),
+        })
+        .collect();
+
+    let mut state = state.lock().unwrap();
+    state.calls.push((method.clone(), params.clone()));
+
+    let torrent = match params.first() {
+        Some(Param::String(hash)) => state.torrents.get(hash),
+        _ => None,
+    };
+
+    match (method.as_str(), torrent) {
+        ("d.directory", Some((directory, _))) => value(&format!("<string>{}</string>", directory)),
+        ("d.is_multi_file", Some((_, multi))) => value(&format!("<i8>{}</i8>", *multi as u8)),
+        ("d.directory" | "d.is_multi_file", None) => fault(-501, "Could not find info-hash."),
+        ("load.raw" | "load.raw_start", _) => value("<i8>0</i8>"),
+        _ => fault(-506, "Method not defined"),
+    }
+}
+
+struct FakeRTorrent {
+    state: SharedState,
+}
+
+impl FakeRTorrent {
+    fn new(torrents: &[(&str, &str, bool)]) -> Self {
+        let torrents = torrents.iter()
+            .map(|(hash, directory, multi)| (hash.to_uppercase(), (directory.to_string(), *multi)))
+            .collect();
+
+        FakeRTorrent { state: Arc::new(Mutex::new(RTorrentState { torrents, calls: Vec::new() })) }
+    }
+
+    /// Serve XML-RPC over HTTP, like behind a web server.
+    async fn http(&self) -> StandIn {
+        let state = Arc::clone(&self.state);
+        StandIn::start(move |request| {
+            if request.path != "/RPC2" || request.header("content-type") != Some("text/xml") {
+                return Response::status(404);
+            }
+
+            Response::ok("text/xml", handle(&state, &request.body))
+        }).await
+    }
+
+    /// Serve XML-RPC over SCGI and return the `host:port`.
+    async fn scgi(&self) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+
+        let state = Arc::clone(&self.state);
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let mut request = Vec::new();
+                let mut chunk = [0u8; 4096];
+
+                // A netstring of headers, followed by a body of CONTENT_LENGTH bytes.
+                loop {
+                    let read = stream.read(&mut chunk).await.unwrap();
+                    request.extend_from_slice(&chunk[..read]);
+
+                    let colon = request.iter().position(|b| *b == b':').unwrap();
+                    let length: usize = std::str::from_utf8(&request[..colon]).unwrap().parse().unwrap();
+                    let headers = String::from_utf8_lossy(&request[colon + 1..(colon + 1 + length).min(request.len())]).into_owned();
+                    let content_length: usize = match headers.split('\0').skip_while(|h| *h != "CONTENT_LENGTH").nth(1) {
+                        Some(content_length) if request.len() > colon + length + 1 => content_length.parse().unwrap(),
+                        _ => continue,
+                    };
+
+                    let body_start = colon + length + 2;
+                    if request.len() >= body_start + content_length {
+                        let response = handle(&state, &request[body_start..body_start + content_length]);
+                        let response = format!("Status: 200 OK\r\nContent-Type: text/xml\r\nContent-Length: {}\r\n\r\n{}", response.len(), response);
+                        stream.write_all(response.as_bytes()).await.unwrap();
+                        stream.shutdown().await.unwrap();
+                        break;
+                    }
+                }
+            }
+        });
+
+        address
+    }
+
+    fn calls(&self, method: &str) -> Vec<Vec<Param>> {
+        self.state.lock().unwrap().calls.iter()
+            .filter(|(m, _)| m == method)
+            .map(|(_, params)| params.clone())
+            .collect()
+    }
+}
+
+fn config(url: String) -> ClientConfig {
+    ClientConfig {
+        name: "rtorrent".to_string(),
+        kind: ClientKind::RTorrent,
+        url,
+        username: None,
+        password: None,
+        category: Some("cross-seed".to_string()),
+        tags: Vec::new(),
+        paused: false,
+        skip_checking: false,
+        verify: false,
+    }
+}
+
+#[tokio::test]
+async fn adds_the_cross_seed_over_http() {
+    let local = TestTorrent::multi(NAME, &FILES);
+    let rtorrent = FakeRTorrent::new(&[(&local.info_hash(), &format!("/data/tv/{}", NAME), true)]);
+    let server = rtorrent.http().await;
+    let client = config(format!("{}/RPC2", server.url)).create_client();
+    let candidate = candidate(&local).await;
+
+    let outcome = clients::inject(client.as_ref(), &local.info_hash(), &candidate).await.unwrap();
+    assert_eq!(outcome, InjectOutcome::Added("/data/tv".to_string()));
+
+    // The hashes are looked up in uppercase.
+    let lookups = rtorrent.calls("d.directory");
+    assert_eq!(lookups[0], [Param::String(local.info_hash().to_uppercase())]);
+
+    assert_eq!(rtorrent.calls("load.raw_start"), [vec![
+        Param::String(String::new()),
+        Param::Base64(candidate.bytes.to_vec()),
+        Param::String("d.directory.set=\"/data/tv\"".to_string()),
+        Param::String("d.custom1.set=\"cross-seed\"".to_string()),
+    ]]);
+}
+
+#[tokio::test]
+async fn looks_up_torrents_over_scgi() {
+    let rtorrent = FakeRTorrent::new(&[("abc", "/data/movies", false), ("def", "/data/tv/Some.Show", true)]);
+    let address = rtorrent.scgi().await;
+    let client = config(format!("scgi://{}", address)).create_client();
+
+    assert_eq!(client.save_path("abc").await.unwrap().as_deref(), Some("/data/movies"));
+    assert_eq!(client.save_path("def").await.unwrap().as_deref(), Some("/data/tv"));
+    assert_eq!(client.save_path("123").await.unwrap(), None);
+}
+
+#[tokio::test]
+async fn adds_paused_torrents_without_starting_them() {
+    let rtorrent = FakeRTorrent::new(&[]);
+    let address = rtorrent.scgi().await;
+    let mut config = config(format!("scgi://{}", address));
+    config.paused = true;
+    config.category = None;
+
+    config.create_client().add_torrent(b"d4:infod4:name1:aee", "/data").await.unwrap();
+
+    assert!(rtorrent.calls("load.raw_start").is_empty());
+    assert_eq!(rtorrent.calls("load.raw")[0][2], Param::String("d.directory.set=\"/data\"".to_string()));
+    assert_eq!(rtorrent.calls("load.raw")[0].len(), 3);
+}
+
+#[tokio::test]
+async fn escapes_backslashes_and_quotes() {
+    let rtorrent = FakeRTorrent::new(&[]);
+    let address = rtorrent.scgi().await;
+    let mut config = config(format!("scgi://{}", address));
+    config.category = Some(r#"tv\"hd""#.to_string());
+
+    config.create_client().add_torrent(b"d4:infod4:name1:aee", r#"D:\data\"tv""#).await.unwrap();
+
+    assert_eq!(rtorrent.calls("load.raw_start")[0][2..], [
+        Param::String(r#"d.directory.set="D:\\data\\\"tv\"""#.to_string()),
+        Param::String(r#"d.custom1.set="tv\\\"hd\"""#.to_string()),
+    ]);
+}
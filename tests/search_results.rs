@@ -0,0 +1,115 @@
+use std::path::Path;
+
+use chrono::DateTime;
+use cross_seed::torznab::{SearchResponse, TorrentResult};
+use rss::Channel;
+
+fn search(name: &str) -> SearchResponse {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/search").join(name);
+    let xml = std::fs::read(path).unwrap();
+    let channel = Channel::read_from(xml.as_slice()).unwrap();
+
+    SearchResponse::from_channel(&channel).unwrap()
+}
+
+fn date(s: &str) -> Option<DateTime<chrono::FixedOffset>> {
+    Some(DateTime::parse_from_rfc3339(s).unwrap())
+}
+
+#[test]
+fn jackett() {
+    let response = search("jackett.xml");
+
+    assert_eq!(response.offset, None);
+    assert_eq!(response.total, None);
+    assert_eq!(response.len(), 2);
+
+    let result: &TorrentResult = &response.results[0];
+    assert_eq!(result.name, "Some.Movie.2019.1080p.BluRay.x264-GRP");
+    assert_eq!(result.link, "http://127.0.0.1:9117/dl/1337x/?jackett_apikey=abcdef&path=Q2ZESjhH&file=Some.Movie.2019.1080p.BluRay.x264-GRP");
+    assert_eq!(result.size, Some(8589934592));
+    assert_eq!(result.categories, [2000, 100001]);
+    assert_eq!(result.pub_date, date("2022-10-01T12:34:56+00:00"));
+    assert_eq!(result.guid.as_deref(), Some("https://1337x.to/torrent/4194361/Some-Movie-2019-1080p-BluRay-x264-GRP/"));
+    assert_eq!(result.comments, result.guid);
+
+    let enclosure = result.enclosure.as_ref().unwrap();
+    assert_eq!(enclosure.url, result.link);
+    assert_eq!(enclosure.length, Some(8589934592));
+    assert_eq!(enclosure.mime_type, "application/x-bittorrent");
+
+    let attributes = &result.attributes;
+    assert_eq!(attributes.seeders, Some(412));
+    assert_eq!(attributes.peers, Some(455));
+    assert_eq!(attributes.infohash.as_deref(), Some("0123456789abcdef0123456789abcdef01234567"));
+    assert!(attributes.magnet_url.as_deref().unwrap().starts_with("magnet:?xt=urn:btih:"));
+    assert_eq!(attributes.download_volume_factor, Some(0.0));
+    assert_eq!(attributes.upload_volume_factor, Some(1.0));
+    assert_eq!(attributes.other.get("minimumseedtime").map(String::as_str), Some("172800"));
+
+    let second = &response.results[1];
+    assert_eq!(second.pub_date, date("2022-09-30T08:00:00+02:00"));
+    assert_eq!(second.attributes.infohash, None);
+    assert_eq!(second.comments, None);
+}
+
+#[test]
+fn prowlarr() {
+    let response = search("prowlarr.xml");
+    assert_eq!(response.len(), 1);
+
+    let result = &response.results[0];
+    assert_eq!(result.name, "Artist Name - Album Title (2019) [FLAC 24bit Lossless / WEB]");
+    assert_eq!(result.size, Some(1073741824));
+    assert_eq!(result.categories, [3040, 100001]);
+    assert_eq!(result.guid.as_deref(), Some("https://orpheus.network/torrents.php?id=1234&torrentid=5678"));
+
+    let attributes = &result.attributes;
+    assert_eq!(attributes.files, Some(14));
+    assert_eq!(attributes.grabs, Some(87));
+    assert_eq!(attributes.tags, ["freeleech", "internal"]);
+    assert!(result.has_tag("freeleech"));
+    assert_eq!(attributes.other.get("genre").map(String::as_str), Some(""));
+}
+
+#[test]
+fn nzbhydra() {
+    let response = search("nzbhydra.xml");
+
+    assert_eq!(response.offset, Some(100));
+    assert_eq!(response.total, Some(250));
+
+    let result = &response.results[0];
+    assert_eq!(result.name, "Jane Doe - A Book Title (2020) [EPUB]");
+    assert_eq!(result.guid.as_deref(), Some("5195722830148911234"));
+    assert_eq!(result.pub_date, date("2022-10-03T10:00:00+00:00"));
+
+    // NZBHydra uses the newznab prefix for the attributes, and names in the category elements.
+    assert_eq!(result.categories, [7000, 7020]);
+    assert_eq!(result.size, Some(5242880));
+    assert_eq!(result.attributes.seeders, Some(8));
+    assert_eq!(result.attributes.peers, Some(9));
+    assert_eq!(result.attributes.other.get("hydraIndexerName").map(String::as_str), Some("MyTracker"));
+}
+
+#[test]
+fn tracker() {
+    let response = search("tracker.xml");
+
+    assert_eq!(response.offset, Some(0));
+    assert_eq!(response.total, Some(1));
+
+    let result = &response.results[0];
+    assert_eq!(result.name, "Show & Tell S02 1080p BluRay x264-GRP");
+    // Only the enclosure has the link and the size.
+    assert_eq!(result.link, "https://tracker.example/torrent/download/1001.0123456789abcdef0123456789abcdef");
+    assert_eq!(result.size, Some(52613349376));
+    assert_eq!(result.categories, [5040]);
+    assert_eq!(result.pub_date, date("2022-10-04T18:30:00+00:00"));
+
+    let attributes = &result.attributes;
+    assert_eq!(attributes.infohash.as_deref(), Some("fedcba9876543210fedcba9876543210fedcba98"));
+    assert_eq!(attributes.imdb.as_deref(), Some("0944947"));
+    assert_eq!(attributes.tvdb_id, Some(121361));
+    assert_eq!(attributes.download_volume_factor, Some(0.5));
+}
@@ -0,0 +1,264 @@
+mod common;
+
+use common::{MockIndexer, MockItem, Response, StandIn, TestTorrent};
+
+use cross_seed::matching::{self, FileList, MatchDecision};
+use cross_seed::torznab::search_parameters::GenericSearchParametersBuilder;
+use cross_seed::torznab::{ApiErrorKind, ClientError, RetryPolicy, SearchCapability, SearchFunction, TorznabClient};
+use futures::TryStreamExt;
+use std::io::Write;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+const API_KEY: &str = "mock-key";
+
+fn quick_retries() -> RetryPolicy {
+    RetryPolicy {
+        max_retries: 2,
+        base_delay_ms: 1,
+        max_delay_secs: 1,
+        disable_after: 3,
+    }
+}
+
+fn query(q: &str) -> cross_seed::torznab::GenericSearchParameters {
+    GenericSearchParametersBuilder::new().query(q.to_string()).build()
+}
+
+#[tokio::test]
+async fn reads_the_capabilities() {
+    let indexer = MockIndexer::start(API_KEY).await;
+
+    let client = TorznabClient::new("mock".to_string(), &indexer.url(), API_KEY).await.unwrap();
+
+    let caps = &client.capabilities;
+    assert_eq!(caps.server.as_ref().and_then(|s| s.identity()), Some("Mock 1.0".to_string()));
+    assert_eq!(caps.limits.and_then(|l| l.max), Some(100));
+    assert!(caps.searching_capabilities.does_support_search(SearchCapability::Movie));
+    assert!(caps.tags.get("internal").is_some());
+}
+
+#[tokio::test]
+async fn searches_and_parses_the_results() {
+    let indexer = MockIndexer::start(API_KEY).await;
+    let torrent = TestTorrent::single("Some.Movie.2019.1080p.mkv", 4_000_000);
+    indexer.add_item(MockItem {
+        torrent: torrent.clone(),
+        size: None,
+        tags: vec!["internal".to_string()],
+    });
+    indexer.add(TestTorrent::single("Other.Movie.2020.720p.mkv", 1_000_000));
+
+    let client = TorznabClient::new("mock".to_string(), &indexer.url(), API_KEY).await.unwrap();
+    let response = client.search(SearchFunction::Search, query("some movie")).await.unwrap();
+
+    assert_eq!(response.total, Some(1));
+    assert_eq!(response.len(), 1);
+
+    let result = &response.results[0];
+    assert_eq!(result.name, "Some.Movie.2019.1080p.mkv");
+    assert_eq!(result.size, Some(4_000_000));
+    assert_eq!(result.attributes.seeders, Some(10));
+    assert_eq!(result.attributes.infohash, Some(torrent.info_hash()));
+    assert!(result.has_tag("internal"));
+
+    let requests = indexer.requests("search");
+    assert_eq!(requests[0].query("q"), Some("some movie"));
+    assert_eq!(requests[0].query("apikey"), Some(API_KEY));
+}
+
+#[tokio::test]
+async fn pages_through_the_results() {
+    let indexer = MockIndexer::start(API_KEY).await;
+    for i in 0..7 {
+        indexer.add(TestTorrent::single(&format!("Show.S01E0{}.mkv", i), 1000).seed(i));
+    }
+
+    let client = TorznabClient::new("mock".to_string(), &indexer.url(), API_KEY).await.unwrap();
+    let generic = GenericSearchParametersBuilder::new().query("show".to_string()).limit(3).build();
+    let results: Vec<_> = client.search_paginated(SearchFunction::Search, generic, 100)
+        .try_collect().await.unwrap();
+
+    assert_eq!(results.len(), 7);
+
+    let offsets: Vec<_> = indexer.requests("search").iter()
+        .map(|request| request.query("offset").unwrap_or("0").to_string())
+        .collect();
+    assert_eq!(offsets, ["0", "3", "6"]);
+}
+
+#[tokio::test]
+async fn api_errors_are_typed_and_fatal_ones_disable_the_indexer() {
+    let indexer = MockIndexer::start(API_KEY).await;
+
+    let error = TorznabClient::new("mock".to_string(), &indexer.url(), "wrong-key").await.unwrap_err();
+    match error {
+        ClientError::ApiError(e) => assert_eq!(e.kind, ApiErrorKind::IncorrectCredentials),
+        e => panic!("unexpected error: {}", e),
+    }
+
+    let client = TorznabClient::new("mock".to_string(), &indexer.url(), API_KEY).await.unwrap();
+    indexer.set_error(101, "Account suspended");
+
+    let error = client.search(SearchFunction::Search, query("x")).await.unwrap_err();
+    assert!(matches!(error, ClientError::ApiError(_)), "{}", error);
+    assert!(client.disabled_reason().is_some());
+
+    let error = client.search(SearchFunction::Search, query("x")).await.unwrap_err();
+    assert!(matches!(error, ClientError::IndexerDisabled(_)), "{}", error);
+}
+
+#[tokio::test]
+async fn retries_unavailable_responses() {
+    let indexer = MockIndexer::start(API_KEY).await;
+    let client = TorznabClient::new("mock".to_string(), &indexer.url(), API_KEY).await.unwrap()
+        .with_retry_policy(quick_retries());
+
+    indexer.fail_with(&[503, 502]);
+    let response = client.search(SearchFunction::Search, query("x")).await.unwrap();
+    assert!(response.is_empty());
+
+    indexer.fail_with(&[503, 503, 503]);
+    let error = client.search(SearchFunction::Search, query("x")).await.unwrap_err();
+    assert!(matches!(error, ClientError::HttpError(_)), "{}", error);
+}
+
+#[tokio::test]
+async fn failures_that_are_not_retried_count_towards_disabling() {
+    let indexer = MockIndexer::start(API_KEY).await;
+    let client = TorznabClient::new("mock".to_string(), &indexer.url(), API_KEY).await.unwrap()
+        .with_retry_policy(quick_retries());
+
+    indexer.fail_with(&[500, 500, 500]);
+    for _ in 0..3 {
+        let error = client.search(SearchFunction::Search, query("x")).await.unwrap_err();
+        assert!(matches!(error, ClientError::HttpError(_)), "{}", error);
+    }
+
+    // Not retried, but disabled after three in a row.
+    assert_eq!(indexer.requests("search").len(), 3);
+    assert!(client.disabled_reason().unwrap().contains("3 failed requests"));
+}
+
+#[tokio::test]
+async fn rejected_credentials_disable_the_indexer() {
+    let indexer = MockIndexer::start(API_KEY).await;
+    let client = TorznabClient::new("mock".to_string(), &indexer.url(), API_KEY).await.unwrap()
+        .with_retry_policy(quick_retries());
+
+    indexer.fail_with(&[403]);
+    client.search(SearchFunction::Search, query("x")).await.unwrap_err();
+
+    let error = client.search(SearchFunction::Search, query("x")).await.unwrap_err();
+    assert!(matches!(error, ClientError::IndexerDisabled(_)), "{}", error);
+}
+
+#[tokio::test]
+async fn a_long_retry_after_is_waited_for() {
+    let indexer = MockIndexer::start(API_KEY).await;
+    let client = TorznabClient::new("mock".to_string(), &indexer.url(), API_KEY).await.unwrap()
+        .with_retry_policy(RetryPolicy { max_delay_secs: 0, ..quick_retries() });
+
+    // Longer than the max delay.
+    indexer.fail_with(&[429]);
+    indexer.set_retry_after(1);
+    let start = Instant::now();
+    client.search(SearchFunction::Search, query("x")).await.unwrap();
+
+    assert!(start.elapsed() >= Duration::from_secs(1));
+    assert_eq!(indexer.requests("search").len(), 2);
+    assert_eq!(client.disabled_reason(), None);
+}
+
+#[tokio::test]
+async fn missing_downloads_dont_disable_the_indexer() {
+    let indexer = MockIndexer::start(API_KEY).await;
+    let client = TorznabClient::new("mock".to_string(), &indexer.url(), API_KEY).await.unwrap()
+        .with_retry_policy(quick_retries());
+
+    for _ in 0..5 {
+        let error = client.download(&format!("{}/download/99.torrent", indexer.server.url)).await.unwrap_err();
+        assert!(matches!(error, ClientError::HttpError(_)), "{}", error);
+    }
+
+    assert_eq!(client.disabled_reason(), None);
+    client.search(SearchFunction::Search, query("x")).await.unwrap();
+}
+
+#[tokio::test]
+async fn bodies_that_break_off_are_retried() {
+    let attempts = Arc::new(AtomicUsize::new(0));
+    let server = {
+        let attempts = Arc::clone(&attempts);
+        StandIn::start(move |_| match attempts.fetch_add(1, Ordering::SeqCst) {
+            0 => Response::ok("application/x-bittorrent", "d4:infod4:name4:testee").cut_off(),
+            _ => Response::ok("application/x-bittorrent", "d4:infod4:name4:testee"),
+        }).await
+    };
+    let client = TorznabClient::new_no_capabilities("mock".to_string(), &server.url, API_KEY)
+        .with_retry_policy(quick_retries());
+
+    let bytes = client.download(&format!("{}/file.torrent", server.url)).await.unwrap();
+    assert_eq!(bytes.as_ref(), b"d4:infod4:name4:testee");
+    assert_eq!(server.requests().len(), 2);
+}
+
+/// Collects the log output of a test.
+#[derive(Clone, Default)]
+struct Logs(Arc<Mutex<Vec<u8>>>);
+
+impl Write for Logs {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn errors_hide_the_api_key() {
+    let logs = Logs::default();
+    let subscriber = {
+        let logs = logs.clone();
+        tracing_subscriber::fmt().with_ansi(false).with_writer(move || logs.clone()).finish()
+    };
+    let _guard = tracing::subscriber::set_default(subscriber);
+
+    let indexer = MockIndexer::start(API_KEY).await;
+    let client = TorznabClient::new("mock".to_string(), &indexer.url(), API_KEY).await.unwrap()
+        .with_retry_policy(RetryPolicy { disable_after: 1, ..quick_retries() });
+
+    indexer.fail_with(&[503, 503, 503]);
+    let error = client.search(SearchFunction::Search, query("x")).await.unwrap_err();
+
+    let logs = String::from_utf8(logs.0.lock().unwrap().clone()).unwrap();
+    let reason = client.disabled_reason().unwrap();
+    assert!(logs.contains("retrying"), "{}", logs);
+    assert!(reason.contains("503"), "{}", reason);
+
+    for message in [error.to_string(), logs, reason] {
+        assert!(!message.contains(API_KEY), "{}", message);
+    }
+}
+
+#[tokio::test]
+async fn downloads_and_matches_a_cross_seed() {
+    let indexer = MockIndexer::start(API_KEY).await;
+    let files = [("Album/01.flac", 30_000_000), ("Album/02.flac", 25_000_000), ("Album/cover.jpg", 200_000)];
+    let local = TestTorrent::multi("Artist - Album (2019) [FLAC]", &files);
+    indexer.add(TestTorrent::multi("Artist - Album (2019) [FLAC]", &files).seed(7));
+
+    let client = TorznabClient::new("mock".to_string(), &indexer.url(), API_KEY).await.unwrap();
+    let response = client.search(SearchFunction::Search, query("artist album")).await.unwrap();
+    let result = response.results.into_iter().next().unwrap();
+
+    let local_files = FileList::from(&local.torrent());
+    let (decision, candidate) = matching::evaluate(&client, &local_files, Some(&local.info_hash()), result).await.unwrap();
+
+    assert_eq!(decision.decision, MatchDecision::Match);
+    assert_eq!(candidate.unwrap().torrent.name, "Artist - Album (2019) [FLAC]");
+    assert_eq!(indexer.downloads().len(), 1);
+}
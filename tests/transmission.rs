@@ -0,0 +1,230 @@
+mod common;
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use common::{candidate, Request, Response, StandIn, TestTorrent};
+
+use cross_seed::clients::{self, ClientConfig, ClientKind, InjectOutcome, TorrentClientError};
+use cross_seed::matching::FileList;
+use serde_json::{json, Value};
+
+const NAME: &str = "Some.Show.S01.1080p.WEB.h264-GRP";
+const FILES: [(&str, u64); 2] = [("Some.Show.S01E01.1080p.WEB.h264-GRP.mkv", 2_000_000_000), ("Some.Show.S01E02.1080p.WEB.h264-GRP.mkv", 2_100_000_000)];
+
+/// `admin:secret` in basic auth.
+const AUTHORIZATION: &str = "Basic YWRtaW46c2VjcmV0";
+
+#[derive(Default)]
+struct TransmissionState {
+    /// Download dirs by info hash.
+    torrents: HashMap<String, String>,
+    /// The torrents returned when no ids are given.
+    listed: Vec<Value>,
+    session_id: String,
+    /// The arguments of the RPC calls, by method.
+    calls: Vec<(String, Value)>,
+}
+
+struct FakeTransmission {
+    server: StandIn,
+    state: Arc<Mutex<TransmissionState>>,
+}
+
+impl FakeTransmission {
+    async fn start(torrents: &[(&str, &str)]) -> Self {
+        let state = Arc::new(Mutex::new(TransmissionState {
+            torrents: torrents.iter().map(|(hash, dir)| (hash.to_string(), dir.to_string())).collect(),
+            session_id: "first".to_string(),
+            ..TransmissionState::default()
+        }));
+
+        let server = {
+            let state = Arc::clone(&state);
+            StandIn::start(move |request| handle(&mut state.lock().unwrap(), request)).await
+        };
+
+        FakeTransmission { server, state }
+    }
+
+    fn config(&self) -> ClientConfig {
+        ClientConfig {
+            name: "transmission".to_string(),
+            kind: ClientKind::Transmission,
+            url: format!("{}/transmission/rpc", self.server.url),
+            username: Some("admin".to_string()),
+            password: Some("secret".to_string()),
+            category: None,
+            tags: vec!["cross-seed".to_string()],
+            paused: false,
+            skip_checking: false,
+            verify: true,
+        }
+    }
+
+    fn calls(&self, method: &str) -> Vec<Value> {
+        self.state.lock().unwrap().calls.iter()
+            .filter(|(m, _)| m == method)
+            .map(|(_, arguments)| arguments.clone())
+            .collect()
+    }
+
+    /// List a torrent with the files of `torrent`.
+    fn list(&self, torrent: &TestTorrent, status: u8, percent_done: f64) {
+        let files: Vec<Value> = torrent.files.iter()
+            .map(|(path, length)| json!({ "name": format!("{}/{}", torrent.name, path), "length": length, "bytesCompleted": 0 }))
+            .collect();
+
+        self.state.lock().unwrap().listed.push(json!({
+            "hashString": torrent.info_hash(),
+            "name": torrent.name,
+            "downloadDir": "/data/tv",
+            "percentDone": percent_done,
+            "status": status,
+            "labels": ["sonarr"],
+            "files": files,
+        }));
+    }
+
+    /// Start a new session, like when Transmission restarts.
+    fn rotate_session(&self) {
+        self.state.lock().unwrap().session_id = "second".to_string();
+    }
+}
+
+fn handle(state: &mut TransmissionState, request: &Request) -> Response {
+    if request.path != "/transmission/rpc" {
+        return Response::status(404);
+    }
+
+    if request.header("authorization") != Some(AUTHORIZATION) {
+        return Response::status(401);
+    }
+
+    if request.header("X-Transmission-Session-Id") != Some(state.session_id.as_str()) {
+        return Response::status(409).header("X-Transmission-Session-Id", &state.session_id);
+    }
+
+    let body: Value = serde_json::from_slice(&request.body).unwrap();
+    let method = body["method"].as_str().unwrap().to_string();
+    let arguments = body["arguments"].clone();
+    state.calls.push((method.clone(), arguments.clone()));
+
+    let response = match method.as_str() {
+        "torrent-get" if arguments.get("ids").is_none() => {
+            json!({ "result": "success", "arguments": { "torrents": state.listed } })
+        },
+        "torrent-get" => {
+            let torrents: Vec<Value> = arguments["ids"].as_array().unwrap().iter()
+                .filter_map(|id| state.torrents.get_key_value(id.as_str().unwrap()))
+                .map(|(hash, dir)| json!({ "hashString": hash, "downloadDir": dir }))
+                .collect();
+            json!({ "result": "success", "arguments": { "torrents": torrents } })
+        },
+        "torrent-add" => json!({
+            "result": "success",
+            "arguments": { "torrent-added": { "id": 7, "hashString": "added", "name": NAME } },
+        }),
+        "torrent-verify" => json!({ "result": "success", "arguments": {} }),
+        _ => json!({ "result": "method name not recognized" }),
+    };
+
+    Response::json(response.to_string())
+}
+
+#[tokio::test]
+async fn adds_the_cross_seed_to_the_download_dir_of_the_original() {
+    let local = TestTorrent::multi(NAME, &FILES);
+    let transmission = FakeTransmission::start(&[(&local.info_hash(), "/data/tv")]).await;
+    let client = transmission.config().create_client();
+    let candidate = candidate(&local).await;
+
+    let outcome = clients::inject(client.as_ref(), &local.info_hash(), &candidate).await.unwrap();
+    assert_eq!(outcome, InjectOutcome::Added("/data/tv".to_string()));
+
+    let adds = transmission.calls("torrent-add");
+    assert_eq!(adds.len(), 1);
+    assert_eq!(adds[0]["download-dir"], "/data/tv");
+    assert_eq!(adds[0]["paused"], false);
+    assert_eq!(adds[0]["labels"], json!(["cross-seed"]));
+    assert_eq!(base64::decode(adds[0]["metainfo"].as_str().unwrap()).unwrap(), candidate.bytes.as_ref());
+
+    let verifies = transmission.calls("torrent-verify");
+    assert_eq!(verifies, [json!({ "ids": ["added"] })]);
+}
+
+#[tokio::test]
+async fn negotiates_the_session_id() {
+    let transmission = FakeTransmission::start(&[("abc", "/data")]).await;
+    let client = transmission.config().create_client();
+
+    assert_eq!(client.save_path("abc").await.unwrap().as_deref(), Some("/data"));
+    assert_eq!(client.save_path("def").await.unwrap(), None);
+
+    transmission.rotate_session();
+    assert_eq!(client.save_path("abc").await.unwrap().as_deref(), Some("/data"));
+
+    // One 409 for each session.
+    let conflicts = transmission.server.requests().iter()
+        .filter(|request| request.header("X-Transmission-Session-Id") != Some("first"))
+        .count();
+    assert_eq!(conflicts, 2);
+    assert_eq!(transmission.calls("torrent-get").len(), 3);
+}
+
+#[tokio::test]
+async fn does_not_verify_unless_configured() {
+    let local = TestTorrent::multi(NAME, &FILES);
+    let transmission = FakeTransmission::start(&[(&local.info_hash(), "/data/tv")]).await;
+    let mut config = transmission.config();
+    config.verify = false;
+    config.paused = true;
+
+    let outcome = clients::inject(config.create_client().as_ref(), &local.info_hash(), &candidate(&local).await).await.unwrap();
+    assert!(matches!(outcome, InjectOutcome::Added(_)));
+
+    assert_eq!(transmission.calls("torrent-add")[0]["paused"], true);
+    assert!(transmission.calls("torrent-verify").is_empty());
+}
+
+#[tokio::test]
+async fn wrong_credentials_fail() {
+    let transmission = FakeTransmission::start(&[]).await;
+    let mut config = transmission.config();
+    config.password = Some("wrong".to_string());
+
+    let error = config.create_client().save_path("abc").await.unwrap_err();
+    assert!(matches!(error, TorrentClientError::LoginFailed), "{}", error);
+}
+
+#[tokio::test]
+async fn lists_the_torrents() {
+    let local = TestTorrent::multi(NAME, &FILES);
+    let transmission = FakeTransmission::start(&[]).await;
+    transmission.list(&local, 6, 1.0);
+    transmission.list(&TestTorrent::multi("Other.Show.S02.1080p.WEB.h264-GRP", &FILES), 4, 0.25);
+    transmission.list(&TestTorrent::single("Stopped.Show.S01E01.1080p.WEB.h264-GRP.mkv", 1_000), 0, 1.0);
+    transmission.list(&TestTorrent::single("Stopped.Show.S01E02.1080p.WEB.h264-GRP.mkv", 1_000), 0, 0.5);
+    transmission.list(&TestTorrent::single("Checked.Show.S01E03.1080p.WEB.h264-GRP.mkv", 1_000), 2, 1.0);
+
+    let torrents = transmission.config().create_client().torrents().await.unwrap();
+    assert_eq!(torrents.len(), 5);
+
+    assert_eq!(torrents[0].info_hash, local.info_hash());
+    assert_eq!(torrents[0].save_path, "/data/tv");
+    assert_eq!(torrents[0].tags, ["sonarr"]);
+    assert_eq!(torrents[0].category, None);
+    assert_eq!(torrents[0].files, FileList::from(&local.torrent()));
+    assert!(torrents[0].is_complete());
+
+    // Still downloading.
+    assert!(!torrents[1].is_complete());
+
+    // Stopped after finishing, stopped while downloading, and being verified.
+    assert!(torrents[2].is_complete());
+    assert!(!torrents[3].is_complete());
+    assert!(!torrents[4].is_complete());
+
+    let gets = transmission.calls("torrent-get");
+    assert_eq!(gets[0].get("ids"), None);
+}